@@ -0,0 +1,217 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+ * Copyright (C) 2023 Alexander Seifarth
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! Workspace configuration file `ram.toml`.
+//!
+//! ```toml
+//! [workspace]
+//! # directories and files with .ram sources relative to ram.toml, default: the directory of ram.toml
+//! roots = ["model", "shared/interfaces.ram"]
+//! ```
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+use toml::Spanned;
+use crate::diagnostic::{Diagnostic, Severity};
+
+/// Name of the workspace configuration file.
+pub const FILE_NAME: &str = "ram.toml";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// Directory containing the configuration file.
+    pub dir: PathBuf,
+    /// Directories and files with sources, already joined to `dir`.
+    pub source_roots: Vec<PathBuf>
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(PathBuf, io::Error),
+    /// The configuration file is invalid, the diagnostics refer to `text`.
+    Invalid{path: PathBuf, text: String, diagnostics: Vec<Diagnostic>}
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            Error::Invalid{path, diagnostics, ..} => {
+                write!(f, "{}: invalid configuration", path.display())?;
+                for d in diagnostics {
+                    write!(f, "\n  {}", d.message)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    workspace: Option<WorkspaceSection>
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WorkspaceSection {
+    roots: Option<Spanned<Vec<Spanned<String>>>>
+}
+
+impl Config {
+
+    /// Returns the configuration used when there is no configuration file in `dir`.
+    pub fn new(dir: &Path) -> Self {
+        Config{dir: dir.to_path_buf(), source_roots: vec![dir.to_path_buf()]}
+    }
+
+    /// Searches `dir` and its ancestors for a configuration file and returns its path.
+    pub fn find(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors().map(|d| d.join(FILE_NAME)).find(|path| path.is_file())
+    }
+
+    /// Reads and validates the configuration file at `path`.
+    pub fn load(path: &Path) -> Result<Config, Error> {
+        let text = fs::read_to_string(path).map_err(|e| Error::Io(path.to_path_buf(), e))?;
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new(".")
+        };
+        Config::parse(dir, &text).map_err(|diagnostics| Error::Invalid{path: path.to_path_buf(), text, diagnostics})
+    }
+
+    /// Parses and validates the text of a configuration file located in `dir`.
+    /// Source roots must exist relative to `dir`.
+    pub fn parse(dir: &Path, text: &str) -> Result<Config, Vec<Diagnostic>> {
+        let file: ConfigFile = toml::from_str(text)
+            .map_err(|e| vec![error(text, e.span().unwrap_or(0..0), e.message())])?;
+        let mut config = Config::new(dir);
+        let mut diagnostics = Vec::new();
+
+        if let Some(roots) = file.workspace.and_then(|w| w.roots) {
+            if roots.get_ref().is_empty() {
+                diagnostics.push(error(text, roots.span(), "`roots` must name at least one source root"));
+            }
+            config.source_roots = roots.into_inner().into_iter()
+                .filter_map(|root| {
+                    let path = dir.join(root.get_ref());
+                    if path.exists() {
+                        Some(path)
+                    } else {
+                        diagnostics.push(error(text, root.span(),
+                            &format!("source root `{}` does not exist", root.get_ref())));
+                        None
+                    }
+                })
+                .collect();
+        }
+        if diagnostics.is_empty() { Ok(config) } else { Err(diagnostics) }
+    }
+}
+
+/// Creates an error diagnostic, `span` is a byte range of `text`.
+fn error(text: &str, span: Range<usize>, message: &str) -> Diagnostic {
+    let position = |offset: usize| text[..offset.min(text.len())].chars().count();
+    Diagnostic{
+        severity: Severity::Error,
+        range: position(span.start)..position(span.end),
+        message: message.trim().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Temporary directory with the subdirectories `model/sub`, removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("ram-config-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(dir.join("model").join("sub")).unwrap();
+            TempDir(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn defaults() {
+        let dir = Path::new("ws");
+        assert_eq!(Config::parse(dir, "").unwrap(), Config::new(dir));
+        assert_eq!(Config::parse(dir, "[workspace]\n").unwrap().source_roots, vec![PathBuf::from("ws")]);
+    }
+
+    #[test]
+    fn source_roots() {
+        let temp = TempDir::new("roots");
+        let dir = &temp.0;
+        let config = Config::parse(dir, "[workspace]\nroots = [\"model\", \"model/sub\"]\n").unwrap();
+        assert_eq!(config.source_roots, vec![dir.join("model"), dir.join("model/sub")]);
+    }
+
+    #[test]
+    fn missing_source_root() {
+        let temp = TempDir::new("missing");
+        let dir = &temp.0;
+        let text = "[workspace]\nroots = [\"model\", \"\u{2018}modle\"]\n";
+        let diagnostics = Config::parse(dir, text).unwrap_err();
+        assert_eq!(diagnostics, vec![Diagnostic{
+            severity: Severity::Error,
+            range: 30..38,
+            message: "source root `\u{2018}modle` does not exist".to_string()
+        }]);
+    }
+
+    #[test]
+    fn invalid_files() {
+        let dir = Path::new("ws");
+        let diagnostics = Config::parse(dir, "[workspace]\nroots = []\n").unwrap_err();
+        assert_eq!(diagnostics[0].range, 20..22);
+
+        let diagnostics = Config::parse(dir, "[workspace]\nroot = [\"a\"]\n").unwrap_err();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range, 12..16);
+        assert!(diagnostics[0].message.starts_with("unknown field `root`"), "{}", diagnostics[0].message);
+
+        let diagnostics = Config::parse(dir, "[workspace]\nroots = 12\n").unwrap_err();
+        assert_eq!(diagnostics[0].range, 20..22);
+
+        let diagnostics = Config::parse(dir, "[workspace\n").unwrap_err();
+        assert_eq!(diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn find_and_load() {
+        let temp = TempDir::new("find");
+        let dir = &temp.0;
+        fs::write(dir.join(FILE_NAME), "[workspace]\nroots = [\"model\"]\n").unwrap();
+        let path = Config::find(&dir.join("model").join("sub")).unwrap();
+        assert_eq!(path, dir.join(FILE_NAME));
+        assert_eq!(Config::load(&path).unwrap().source_roots, vec![dir.join("model")]);
+
+        fs::write(dir.join(FILE_NAME), "[workspace]\nroots = [\"none\"]\n").unwrap();
+        match Config::load(&path) {
+            Err(Error::Invalid{text, diagnostics, ..}) => {
+                assert_eq!(text, "[workspace]\nroots = [\"none\"]\n");
+                assert_eq!(diagnostics.len(), 1);
+            }
+            r => panic!("unexpected result {:?}", r)
+        }
+    }
+}
@@ -0,0 +1,152 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+ * Copyright (C) 2023 Alexander Seifarth
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+use std::fmt;
+use std::rc::Rc;
+use super::SyntaxKind;
+
+/// Leaf of the green tree holding the text of one token.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GreenToken {
+    kind: SyntaxKind,
+    text: String,
+    len: usize,
+    // message of the lexer error for tokens of kind `SyntaxKind::Error`
+    error: Option<String>
+}
+
+impl GreenToken {
+
+    /// Creates a new token of the given kind and text.
+    pub fn new(kind: SyntaxKind, text: &str) -> Self {
+        GreenToken{kind, text: text.to_string(), len: text.chars().count(), error: None}
+    }
+
+    /// Creates a new token of kind `SyntaxKind::Error` for text the lexer rejected with `message`.
+    pub fn new_error(text: &str, message: &str) -> Self {
+        GreenToken{error: Some(message.to_string()), ..GreenToken::new(SyntaxKind::Error, text)}
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the lexer error message of an error token - `None` for all other tokens.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Returns the length of the text in UTF code points.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Inner node of the green tree.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GreenNode {
+    kind: SyntaxKind,
+    len: usize,
+    children: Vec<GreenElement>
+}
+
+impl GreenNode {
+
+    /// Creates a new node of the given kind from its children.
+    pub fn new(kind: SyntaxKind, children: Vec<GreenElement>) -> Self {
+        let len = children.iter().map(|c| c.len()).sum();
+        GreenNode{kind, len, children}
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    /// Returns the length of the text covered by this node in UTF code points.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn children(&self) -> &[GreenElement] {
+        &self.children
+    }
+
+    /// Returns a copy of this node with the child at `index` replaced by `child`.
+    /// # Panics
+    /// Panics when `index` is out of bounds.
+    pub fn replace_child(&self, index: usize, child: GreenElement) -> GreenNode {
+        let mut children = self.children.clone();
+        children[index] = child;
+        GreenNode::new(self.kind, children)
+    }
+}
+
+impl fmt::Display for GreenNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for child in &self.children {
+            match child {
+                GreenElement::Node(node) => node.fmt(f)?,
+                GreenElement::Token(token) => f.write_str(token.text())?
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>)
+}
+
+impl GreenElement {
+
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            GreenElement::Node(node) => node.kind(),
+            GreenElement::Token(token) => token.kind()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.len(),
+            GreenElement::Token(token) => token.len()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn into_node(self) -> Option<Rc<GreenNode>> {
+        match self {
+            GreenElement::Node(node) => Some(node),
+            GreenElement::Token(_) => None
+        }
+    }
+
+    pub fn into_token(self) -> Option<Rc<GreenToken>> {
+        match self {
+            GreenElement::Node(_) => None,
+            GreenElement::Token(token) => Some(token)
+        }
+    }
+}
@@ -0,0 +1,262 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+ * Copyright (C) 2023 Alexander Seifarth
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! Lossless concrete syntax tree (CST).
+//!
+//! The tree is split in two layers:
+//! * the green tree (`GreenNode`, `GreenToken`) is immutable, holds the text and only knows the
+//!   length of its elements, so unchanged subtrees can be shared between versions of a document,
+//! * the red tree (`SyntaxNode`, `SyntaxToken`) is a cursor on top of the green tree that adds
+//!   parent links and absolute offsets.
+//!
+//! Whitespace, comments and text the lexer could not tokenize are kept as tokens, so the text of
+//! the root node is always identical to the parsed text. Until the DSL grammar is available the
+//! tree only groups tokens by matching braces, brackets and parentheses.
+//! Offsets and lengths are counted in UTF code points like `lexer::token::Token::position`.
+mod green;
+mod red;
+mod reparse;
+
+#[cfg(test)]
+mod tests;
+
+use std::rc::Rc;
+use crate::lexer::Lexer;
+use crate::lexer::token::TokenKind;
+
+pub use green::*;
+pub use red::*;
+pub use reparse::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    // nodes
+    Root,
+    BraceGroup,         // { ... }
+    BracketGroup,       // [ ... ]
+    DoubleBracketGroup, // [[ ... ]]
+    ParensGroup,        // ( ... )
+
+    // tokens
+    Error,
+    Whitespace,
+    Comment,
+    Identifier,
+    Integer,
+    Float,
+    BracesLeft,
+    BracesRight,
+    Equals,
+    Assign,
+    Colon,
+    ExclMark,
+    NotEqual,
+    Greater,
+    GreaterThan,
+    Less,
+    LessThan,
+    Plus,
+    Increment,
+    Minus,
+    Decrement,
+    Star,
+    Multiply,
+    Slash,
+    Divide,
+    Ampersand,
+    LogicalAnd,
+    Vert,
+    LogicalOr,
+    PerCent,
+    Hash,
+    Dollar,
+    DoubleQuote,
+    SingleQuote,
+    SingleQuote2,
+    QuestionTag,
+    Comma,
+    Dot,
+    Range,
+    RangeIncl,
+    Tilde,
+    BracketLeft,
+    DoubleBracketLeft,
+    BracketRight,
+    DoubleBracketRight,
+    ParensLeft,
+    ParensRight,
+}
+
+impl SyntaxKind {
+
+    /// Returns `true` for whitespace and comments.
+    pub fn is_trivia(self) -> bool {
+        matches!(self, SyntaxKind::Whitespace | SyntaxKind::Comment)
+    }
+
+    /// Returns the group node kind a token of this kind opens - `None` if it opens no group.
+    pub fn opened_group(self) -> Option<SyntaxKind> {
+        match self {
+            SyntaxKind::BracesLeft => Some(SyntaxKind::BraceGroup),
+            SyntaxKind::BracketLeft => Some(SyntaxKind::BracketGroup),
+            SyntaxKind::DoubleBracketLeft => Some(SyntaxKind::DoubleBracketGroup),
+            SyntaxKind::ParensLeft => Some(SyntaxKind::ParensGroup),
+            _ => None
+        }
+    }
+
+    /// Returns the group node kind a token of this kind closes - `None` if it closes no group.
+    pub fn closed_group(self) -> Option<SyntaxKind> {
+        match self {
+            SyntaxKind::BracesRight => Some(SyntaxKind::BraceGroup),
+            SyntaxKind::BracketRight => Some(SyntaxKind::BracketGroup),
+            SyntaxKind::DoubleBracketRight => Some(SyntaxKind::DoubleBracketGroup),
+            SyntaxKind::ParensRight => Some(SyntaxKind::ParensGroup),
+            _ => None
+        }
+    }
+}
+
+impl From<&TokenKind<'_>> for SyntaxKind {
+    fn from(kind: &TokenKind) -> Self {
+        match kind {
+            TokenKind::Identifier(_) => SyntaxKind::Identifier,
+            TokenKind::Integer(_, _) => SyntaxKind::Integer,
+            TokenKind::Float(_, _) => SyntaxKind::Float,
+            TokenKind::Whitespace(_) => SyntaxKind::Whitespace,
+            TokenKind::Comment(_) => SyntaxKind::Comment,
+            TokenKind::BracesLeft => SyntaxKind::BracesLeft,
+            TokenKind::BracesRight => SyntaxKind::BracesRight,
+            TokenKind::Equals => SyntaxKind::Equals,
+            TokenKind::Assign => SyntaxKind::Assign,
+            TokenKind::Colon => SyntaxKind::Colon,
+            TokenKind::ExclMark => SyntaxKind::ExclMark,
+            TokenKind::NotEqual => SyntaxKind::NotEqual,
+            TokenKind::Greater => SyntaxKind::Greater,
+            TokenKind::GreaterThan => SyntaxKind::GreaterThan,
+            TokenKind::Less => SyntaxKind::Less,
+            TokenKind::LessThan => SyntaxKind::LessThan,
+            TokenKind::Plus => SyntaxKind::Plus,
+            TokenKind::Increment => SyntaxKind::Increment,
+            TokenKind::Minus => SyntaxKind::Minus,
+            TokenKind::Decrement => SyntaxKind::Decrement,
+            TokenKind::Star => SyntaxKind::Star,
+            TokenKind::Multiply => SyntaxKind::Multiply,
+            TokenKind::Slash => SyntaxKind::Slash,
+            TokenKind::Divide => SyntaxKind::Divide,
+            TokenKind::Ampersand => SyntaxKind::Ampersand,
+            TokenKind::LogicalAnd => SyntaxKind::LogicalAnd,
+            TokenKind::Vert => SyntaxKind::Vert,
+            TokenKind::LogicalOr => SyntaxKind::LogicalOr,
+            TokenKind::PerCent => SyntaxKind::PerCent,
+            TokenKind::Hash => SyntaxKind::Hash,
+            TokenKind::Dollar => SyntaxKind::Dollar,
+            TokenKind::DoubleQuote => SyntaxKind::DoubleQuote,
+            TokenKind::SingleQuote => SyntaxKind::SingleQuote,
+            TokenKind::SingleQuote2 => SyntaxKind::SingleQuote2,
+            TokenKind::QuestionTag => SyntaxKind::QuestionTag,
+            TokenKind::Comma => SyntaxKind::Comma,
+            TokenKind::Dot => SyntaxKind::Dot,
+            TokenKind::Range => SyntaxKind::Range,
+            TokenKind::RangeIncl => SyntaxKind::RangeIncl,
+            TokenKind::Tilde => SyntaxKind::Tilde,
+            TokenKind::BracketLeft => SyntaxKind::BracketLeft,
+            TokenKind::DoubleBracketLeft => SyntaxKind::DoubleBracketLeft,
+            TokenKind::BracketRight => SyntaxKind::BracketRight,
+            TokenKind::DoubleBracketRight => SyntaxKind::DoubleBracketRight,
+            TokenKind::ParensLeft => SyntaxKind::ParensLeft,
+            TokenKind::ParensRight => SyntaxKind::ParensRight,
+        }
+    }
+}
+
+/// Parses the text into a syntax tree and returns its root node.
+pub fn parse(text: &str) -> SyntaxNode {
+    SyntaxNode::new_root(build_green(text))
+}
+
+/// Builds the green tree for the given text.
+/// A closing token that does not match the innermost open group is kept as a plain token,
+/// groups still open at the end of the text are closed without a closing token.
+pub fn build_green(text: &str) -> Rc<GreenNode> {
+    let mut builder = Builder::new(text, Lexer::new_from_str_with_trivia(text));
+    while builder.step() {}
+    builder.finish()
+}
+
+/// Builds green nodes from the tokens of a lexer in trivia mode.
+struct Builder<'a> {
+    text: &'a str,
+    lexer: Lexer<'a>,
+    start: usize,
+    position: usize,
+    stack: Vec<(SyntaxKind, Vec<GreenElement>)>
+}
+
+impl<'a> Builder<'a> {
+
+    fn new(text: &'a str, lexer: Lexer<'a>) -> Self {
+        let state = lexer.state();
+        Builder{
+            text,
+            lexer,
+            start: state.offset(),
+            position: state.position(),
+            stack: vec![(SyntaxKind::Root, Vec::new())]
+        }
+    }
+
+    /// Adds the next token to the tree - returns `false` at the end of the text.
+    fn step(&mut self) -> bool {
+        let token = match self.lexer.next() {
+            None => return false,
+            Some(token) => token
+        };
+        let end = self.lexer.offset();
+        let text = &self.text[self.start..end];
+        let (kind, green) = match token {
+            Ok(token) => {
+                let kind = SyntaxKind::from(&token.kind);
+                (kind, GreenToken::new(kind, text))
+            }
+            Err(e) => (SyntaxKind::Error, GreenToken::new_error(text, &e.to_string()))
+        };
+        self.start = end;
+        self.position += green.len();
+        let token = GreenElement::Token(Rc::new(green));
+
+        if let Some(group) = kind.opened_group() {
+            self.stack.push((group, vec![token]));
+            return true
+        }
+        self.stack.last_mut().unwrap().1.push(token);
+        if self.depth() > 0 && kind.closed_group() == Some(self.stack.last().unwrap().0) {
+            self.close_group();
+        }
+        true
+    }
+
+    /// Returns the number of open groups.
+    fn depth(&self) -> usize {
+        self.stack.len() - 1
+    }
+
+    fn close_group(&mut self) {
+        let (kind, children) = self.stack.pop().unwrap();
+        self.stack.last_mut().unwrap().1.push(GreenElement::Node(Rc::new(GreenNode::new(kind, children))));
+    }
+
+    /// Closes all open groups and returns the root node.
+    fn finish(mut self) -> Rc<GreenNode> {
+        while self.depth() > 0 {
+            self.close_group();
+        }
+        let (kind, children) = self.stack.pop().unwrap();
+        Rc::new(GreenNode::new(kind, children))
+    }
+}
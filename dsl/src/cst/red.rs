@@ -0,0 +1,217 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+ * Copyright (C) 2023 Alexander Seifarth
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+use std::ops::Range;
+use std::rc::Rc;
+use super::{GreenElement, GreenNode, GreenToken, SyntaxKind};
+
+/// Node of the red tree - a green node together with its parent and absolute offset.
+/// Cloning is cheap, all clones refer to the same node.
+#[derive(Clone, Debug)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+#[derive(Debug)]
+struct NodeData {
+    green: Rc<GreenNode>,
+    parent: Option<SyntaxNode>,
+    index: usize,
+    offset: usize
+}
+
+impl SyntaxNode {
+
+    /// Creates the root of a red tree for the given green tree.
+    pub fn new_root(green: Rc<GreenNode>) -> Self {
+        SyntaxNode(Rc::new(NodeData{green, parent: None, index: 0, offset: 0}))
+    }
+
+    pub fn green(&self) -> &Rc<GreenNode> {
+        &self.0.green
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.green.kind()
+    }
+
+    pub fn parent(&self) -> Option<SyntaxNode> {
+        self.0.parent.clone()
+    }
+
+    /// Returns the index of this node within its parent's children.
+    pub fn index(&self) -> usize {
+        self.0.index
+    }
+
+    /// Returns the offset of this node from the start of the text.
+    pub fn offset(&self) -> usize {
+        self.0.offset
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.offset()..self.offset() + self.0.green.len()
+    }
+
+    pub fn text(&self) -> String {
+        self.0.green.to_string()
+    }
+
+    /// Returns the direct children of this node, nodes and tokens in text order.
+    pub fn children_with_tokens(&self) -> Vec<SyntaxElement> {
+        let mut offset = self.offset();
+        self.0.green.children().iter().enumerate()
+            .map(|(index, child)| {
+                let element = match child {
+                    GreenElement::Node(green) => SyntaxElement::Node(SyntaxNode(Rc::new(NodeData{
+                        green: green.clone(), parent: Some(self.clone()), index, offset
+                    }))),
+                    GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken{
+                        green: green.clone(), parent: self.clone(), index, offset
+                    })
+                };
+                offset += child.len();
+                element
+            })
+            .collect()
+    }
+
+    /// Returns the direct child nodes of this node.
+    pub fn children(&self) -> Vec<SyntaxNode> {
+        self.children_with_tokens().into_iter()
+            .filter_map(|e| match e {
+                SyntaxElement::Node(node) => Some(node),
+                SyntaxElement::Token(_) => None
+            })
+            .collect()
+    }
+
+    /// Returns all tokens in this subtree in text order.
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+        for child in self.children_with_tokens() {
+            match child {
+                SyntaxElement::Node(node) => tokens.append(&mut node.tokens()),
+                SyntaxElement::Token(token) => tokens.push(token)
+            }
+        }
+        tokens
+    }
+
+    /// Returns the token covering `offset` - `None` if the offset is outside of this node.
+    pub fn token_at_offset(&self, offset: usize) -> Option<SyntaxToken> {
+        for child in self.children_with_tokens() {
+            match child {
+                SyntaxElement::Node(node) if node.range().contains(&offset) => return node.token_at_offset(offset),
+                SyntaxElement::Token(token) if token.range().contains(&offset) => return Some(token),
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Returns the innermost node that completely covers `range`.
+    pub fn covering_node(&self, range: Range<usize>) -> Option<SyntaxNode> {
+        let own = self.range();
+        if range.start < own.start || range.end > own.end {
+            return None
+        }
+        for child in self.children() {
+            if let Some(node) = child.covering_node(range.clone()) {
+                return Some(node)
+            }
+        }
+        Some(self.clone())
+    }
+
+    /// Replaces this node by `green` and returns the green tree of the new root.
+    /// The old tree is not modified, all elements outside of the path to the root are shared.
+    pub fn replace_with(&self, green: Rc<GreenNode>) -> Rc<GreenNode> {
+        match self.parent() {
+            None => green,
+            Some(parent) => parent.replace_child(self.index(), GreenElement::Node(green))
+        }
+    }
+
+    fn replace_child(&self, index: usize, child: GreenElement) -> Rc<GreenNode> {
+        self.replace_with(Rc::new(self.0.green.replace_child(index, child)))
+    }
+}
+
+/// Token of the red tree - a green token together with its parent and absolute offset.
+#[derive(Clone, Debug)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    parent: SyntaxNode,
+    index: usize,
+    offset: usize
+}
+
+impl SyntaxToken {
+
+    pub fn green(&self) -> &Rc<GreenToken> {
+        &self.green
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.green.kind()
+    }
+
+    pub fn text(&self) -> &str {
+        self.green.text()
+    }
+
+    /// Returns the lexer error message of an error token - `None` for all other tokens.
+    pub fn error(&self) -> Option<&str> {
+        self.green.error()
+    }
+
+    pub fn parent(&self) -> SyntaxNode {
+        self.parent.clone()
+    }
+
+    /// Returns the index of this token within its parent's children.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the offset of this token from the start of the text.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.green.len()
+    }
+
+    /// Replaces this token by `green` and returns the green tree of the new root.
+    pub fn replace_with(&self, green: GreenToken) -> Rc<GreenNode> {
+        self.parent.replace_child(self.index, GreenElement::Token(Rc::new(green)))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken)
+}
+
+impl SyntaxElement {
+
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            SyntaxElement::Node(node) => node.kind(),
+            SyntaxElement::Token(token) => token.kind()
+        }
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        match self {
+            SyntaxElement::Node(node) => node.range(),
+            SyntaxElement::Token(token) => token.range()
+        }
+    }
+}
@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+ * Copyright (C) 2023 Alexander Seifarth
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+use std::ops::Range;
+use std::rc::Rc;
+use crate::lexer::{Lexer, LexerState};
+use super::{build_green, Builder, GreenElement, GreenNode, SyntaxKind, SyntaxNode};
+
+/// A change of a text: the code points in `range` are replaced by `text`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub text: String
+}
+
+impl TextEdit {
+
+    pub fn new(range: Range<usize>, text: &str) -> Self {
+        TextEdit{range, text: text.to_string()}
+    }
+
+    /// Returns `text` with this edit applied.
+    /// # Panics
+    /// Panics when the range is outside of `text`.
+    pub fn apply(&self, text: &str) -> String {
+        let offset = |position| text.char_indices().map(|(i, _)| i).chain([text.len()]).nth(position).unwrap();
+        let mut result = text.to_string();
+        result.replace_range(offset(self.range.start)..offset(self.range.end), &self.text);
+        result
+    }
+}
+
+/// Returns the green tree for `new_text`, which must be the text of `root` with `edit` applied.
+/// Only the innermost group around the edit is lexed and built again, provided it is still a single
+/// closed group of the same kind afterwards - otherwise the next enclosing group is tried and
+/// finally the whole text is parsed. All subtrees outside of the rebuilt group are shared with
+/// the old tree.
+pub fn reparse(root: &SyntaxNode, new_text: &str, edit: &TextEdit) -> Rc<GreenNode> {
+    // include the tokens touching the edit, they may merge with the inserted text
+    let around = edit.range.start.saturating_sub(1)..edit.range.end + 1;
+    let mut candidate = root.covering_node(around);
+    while let Some(node) = candidate {
+        if node.kind() == SyntaxKind::Root {
+            break
+        }
+        if let Some(green) = reparse_group(&node, new_text, edit) {
+            return node.replace_with(green)
+        }
+        candidate = node.parent();
+    }
+    build_green(new_text)
+}
+
+fn reparse_group(node: &SyntaxNode, new_text: &str, edit: &TextEdit) -> Option<Rc<GreenNode>> {
+    let old_closing = match node.green().children().last() {
+        Some(GreenElement::Token(token)) if token.kind().closed_group() == Some(node.kind()) => token.clone(),
+        _ => return None
+    };
+    let new_end = node.range().end - edit.range.len() + edit.text.chars().count();
+    let offset = new_text.char_indices().nth(node.offset())?.0;
+
+    let lexer = Lexer::new_from_state(new_text, LexerState::new(offset, node.offset(), true));
+    let mut builder = Builder::new(new_text, lexer);
+    if !builder.step() || builder.depth() != 1 || builder.stack[1].0 != node.kind() {
+        return None
+    }
+    while builder.depth() > 0 {
+        if builder.position >= new_end || !builder.step() {
+            return None
+        }
+    }
+    let group = builder.stack[0].1.pop()?.into_node()?;
+    let new_closing = group.children().last()?.clone().into_token()?;
+    if builder.position != new_end || new_closing.text() != old_closing.text() {
+        return None
+    }
+    Some(group)
+}
@@ -0,0 +1,166 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+ * Copyright (C) 2023 Alexander Seifarth
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+use crate::cst::*;
+
+fn kinds(node: &SyntaxNode) -> Vec<SyntaxKind> {
+    node.children_with_tokens().iter().map(|e| e.kind()).collect()
+}
+
+#[test]
+fn lossless() {
+    let texts = [
+        "",
+        "  \n\t",
+        "a { b = 0x12 // comment\n  c: 1.5e3 } /* block\n comment */",
+        "x @ \u{2018} 0x 12'",
+        "{ ( [ [[ a ]] ] ) }",
+        "} ) a { ( ",
+        "/* not terminated",
+    ];
+    for text in texts {
+        assert_eq!(parse(text).text(), text);
+        assert_eq!(parse(text).range(), 0..text.chars().count());
+    }
+}
+
+#[test]
+fn groups() {
+    use SyntaxKind::*;
+    let root = parse("a { b ( c ) } [[x]]");
+    assert_eq!(root.kind(), Root);
+    assert_eq!(kinds(&root), vec![Identifier, Whitespace, BraceGroup, Whitespace, DoubleBracketGroup]);
+
+    let braces = &root.children()[0];
+    assert_eq!(braces.range(), 2..13);
+    assert_eq!(kinds(braces), vec![BracesLeft, Whitespace, Identifier, Whitespace, ParensGroup, Whitespace, BracesRight]);
+    assert_eq!(braces.children()[0].text(), "( c )");
+    assert_eq!(braces.children()[0].parent().unwrap().range(), 2..13);
+}
+
+#[test]
+fn unbalanced_groups() {
+    use SyntaxKind::*;
+    let root = parse("] { ( ]");
+    assert_eq!(kinds(&root), vec![BracketRight, Whitespace, BraceGroup]);
+    let braces = &root.children()[0];
+    assert_eq!(kinds(braces), vec![BracesLeft, Whitespace, ParensGroup]);
+    assert_eq!(kinds(&braces.children()[0]), vec![ParensLeft, Whitespace, BracketRight]);
+}
+
+#[test]
+fn trivia_and_errors() {
+    use SyntaxKind::*;
+    let root = parse("a // c\n@ /* d */");
+    let tokens: Vec<(SyntaxKind, String)> = root.tokens().iter()
+        .map(|t| (t.kind(), t.text().to_string()))
+        .collect();
+    assert_eq!(tokens, vec![
+        (Identifier, "a".to_string()),
+        (Whitespace, " ".to_string()),
+        (Comment, "// c".to_string()),
+        (Whitespace, "\n".to_string()),
+        (Error, "@".to_string()),
+        (Whitespace, " ".to_string()),
+        (Comment, "/* d */".to_string()),
+    ]);
+    assert_eq!(root.tokens()[4].error(), Some("unknown token `@`"));
+    assert_eq!(root.tokens()[0].error(), None);
+}
+
+#[test]
+fn token_at_offset() {
+    let root = parse("\u{2018}a { bc }");
+    let token = root.token_at_offset(5).unwrap();
+    assert_eq!(token.text(), "bc");
+    assert_eq!(token.range(), 5..7);
+    assert_eq!(token.parent().kind(), SyntaxKind::BraceGroup);
+    assert_eq!(root.token_at_offset(0).unwrap().kind(), SyntaxKind::Error);
+    assert!(root.token_at_offset(10).is_none());
+}
+
+#[test]
+fn covering_node() {
+    let root = parse("a { b ( c ) } d");
+    assert_eq!(root.covering_node(8..9).unwrap().kind(), SyntaxKind::ParensGroup);
+    assert_eq!(root.covering_node(4..9).unwrap().kind(), SyntaxKind::BraceGroup);
+    assert_eq!(root.covering_node(0..15).unwrap().kind(), SyntaxKind::Root);
+    assert!(root.covering_node(0..16).is_none());
+}
+
+#[test]
+fn replace_token_keeps_layout() {
+    let text = "a {\n    // keep me\n    b = 1\n}\n";
+    let root = parse(text);
+    let token = root.tokens().into_iter().find(|t| t.text() == "b").unwrap();
+    let new_root = SyntaxNode::new_root(token.replace_with(GreenToken::new(SyntaxKind::Identifier, "longer")));
+    assert_eq!(new_root.text(), "a {\n    // keep me\n    longer = 1\n}\n");
+    assert_eq!(root.text(), text);
+    assert_eq!(new_root.children()[0].range(), 2..35);
+}
+
+#[test]
+fn replace_node_shares_siblings() {
+    let root = parse("{ a } { b }");
+    let groups = root.children();
+    let new_green = groups[1].replace_with(build_green("{ c }").children()[0].clone().into_node().unwrap());
+    assert_eq!(new_green.to_string(), "{ a } { c }");
+    match (&root.green().children()[0], &new_green.children()[0]) {
+        (GreenElement::Node(old), GreenElement::Node(new)) => assert!(std::rc::Rc::ptr_eq(old, new)),
+        _ => panic!("expected group nodes")
+    }
+}
+
+fn check_reparse(text: &str, edit: &TextEdit) {
+    let new_text = edit.apply(text);
+    let incremental = reparse(&parse(text), &new_text, edit);
+    assert_eq!(incremental, build_green(&new_text), "text: {:?}, edit: {:?}", text, edit);
+}
+
+#[test]
+fn text_edit() {
+    assert_eq!(TextEdit::new(1..2, "xy").apply("a\u{2018}b"), "axyb");
+    assert_eq!(TextEdit::new(3..3, "c").apply("a\u{2018}b"), "a\u{2018}bc");
+    assert_eq!(TextEdit::new(0..3, "").apply("a\u{2018}b"), "");
+}
+
+#[test]
+fn reparse_reuses_siblings() {
+    let text = "a { b = 1 }\nc { d ( e ) }\n";
+    let edit = TextEdit::new(20..21, "ff");
+    let old = parse(text);
+    let new = reparse(&old, &edit.apply(text), &edit);
+    assert_eq!(new.to_string(), "a { b = 1 }\nc { d ( ff ) }\n");
+    let old_children = old.green().children();
+    let new_children = new.children();
+    match (&old_children[2], &new_children[2]) {
+        (GreenElement::Node(o), GreenElement::Node(n)) => assert!(std::rc::Rc::ptr_eq(o, n)),
+        _ => panic!("expected group nodes")
+    }
+}
+
+#[test]
+fn reparse_matches_full_parse() {
+    let texts = [
+        "a { b = 1 }\nc { d ( e ) [x] } // tail\n",
+        "{ [[ a ]] /* c } */ ( 0x1f 2.5 ) }",
+        "{ a } ] { ( } b",
+        "x { y\u{2018} { z } }",
+    ];
+    let inserts = ["", "x", " ", "\n", "{", "}", "(", ")", "[", "]", "[[", "]]", "/*", "*/", "//", "/", "*", "=", ".", "0x", "'", "\u{2018}"];
+    for text in texts {
+        let len = text.chars().count();
+        for start in 0..=len {
+            for end in start..=(start + 2).min(len) {
+                for insert in inserts {
+                    check_reparse(text, &TextEdit::new(start..end, insert));
+                }
+            }
+        }
+    }
+}
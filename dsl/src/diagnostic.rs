@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+ * Copyright (C) 2023 Alexander Seifarth
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+use std::fmt;
+use std::ops::Range;
+use crate::cst::{self, SyntaxKind, SyntaxNode};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Error,
+    Warning
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => f.write_str("error"),
+            Severity::Warning => f.write_str("warning")
+        }
+    }
+}
+
+/// A finding in a text, `range` is given in UTF code points like `lexer::token::Token::position`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub range: Range<usize>,
+    pub message: String
+}
+
+/// Checks the text and returns the found diagnostics in text order.
+/// For now only lexical errors are reported.
+pub fn check(text: &str) -> Vec<Diagnostic> {
+    check_syntax(&cst::parse(text))
+}
+
+/// Returns the diagnostics for the syntax tree in text order.
+pub fn check_syntax(root: &SyntaxNode) -> Vec<Diagnostic> {
+    root.tokens().into_iter()
+        .filter(|token| token.kind() == SyntaxKind::Error)
+        .map(|token| Diagnostic{
+            severity: Severity::Error,
+            range: token.range(),
+            message: token.error().unwrap_or_default().to_string()
+        })
+        .collect()
+}
+
+/// Converts positions in UTF code points into zero based line and column numbers.
+pub struct LineIndex {
+    line_starts: Vec<usize>
+}
+
+impl LineIndex {
+
+    pub fn new(text: &str) -> Self {
+        let mut line_starts = vec![0usize];
+        line_starts.extend(text.chars().enumerate().filter(|(_, ch)| *ch == '\n').map(|(i, _)| i + 1));
+        LineIndex{line_starts}
+    }
+
+    /// Returns the zero based line and column (in UTF code points) of `position`.
+    pub fn line_column(&self, position: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|start| *start <= position) - 1;
+        (line, position - self.line_starts[line])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lexer_errors() {
+        let diagnostics = check("a 0x @ b\n0b11' /* c");
+        assert_eq!(diagnostics, vec![
+            Diagnostic{severity: Severity::Error, range: 2..4, message: "integer literal `0x` has no digits".to_string()},
+            Diagnostic{severity: Severity::Error, range: 5..6, message: "unknown token `@`".to_string()},
+            Diagnostic{severity: Severity::Error, range: 9..14, message: "integer literal `0b11'` ends with a digit separator".to_string()},
+            Diagnostic{severity: Severity::Error, range: 15..19, message: "block comment is not terminated".to_string()},
+        ]);
+        assert!(check("a { b = 1 } // ok").is_empty());
+    }
+
+    #[test]
+    fn line_index() {
+        let index = LineIndex::new("ab\n\u{2018}c\n\nd");
+        assert_eq!(index.line_column(0), (0, 0));
+        assert_eq!(index.line_column(2), (0, 2));
+        assert_eq!(index.line_column(3), (1, 0));
+        assert_eq!(index.line_column(4), (1, 1));
+        assert_eq!(index.line_column(6), (2, 0));
+        assert_eq!(index.line_column(7), (3, 0));
+        assert_eq!(index.line_column(8), (3, 1));
+    }
+}
@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+ * Copyright (C) 2023 Alexander Seifarth
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! Semantic token data for editors, see the LSP `textDocument/semanticTokens` request.
+//! Lines and columns are zero based, columns and lengths are counted in UTF-16 code units as
+//! required by LSP.
+use crate::lexer::Lexer;
+use crate::lexer::token::TokenKind;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SemanticTokenType {
+    Identifier,
+    Number,
+    Operator,
+    Comment,
+}
+
+impl SemanticTokenType {
+
+    /// Token type names in the order of `index()` - to be sent as legend to the client.
+    pub const LEGEND: [&'static str; 4] = ["variable", "number", "operator", "comment"];
+
+    /// Returns the index of this type in `LEGEND`.
+    pub fn index(self) -> u32 {
+        match self {
+            SemanticTokenType::Identifier => 0,
+            SemanticTokenType::Number => 1,
+            SemanticTokenType::Operator => 2,
+            SemanticTokenType::Comment => 3,
+        }
+    }
+
+    /// Returns the LSP name of this type.
+    pub fn name(self) -> &'static str {
+        Self::LEGEND[self.index() as usize]
+    }
+}
+
+/// Returns the semantic token type for a token kind - `None` for whitespace and delimiters.
+pub fn token_type(kind: &TokenKind) -> Option<SemanticTokenType> {
+    match kind {
+        TokenKind::Identifier(_) => Some(SemanticTokenType::Identifier),
+        TokenKind::Integer(_, _) | TokenKind::Float(_, _) => Some(SemanticTokenType::Number),
+        TokenKind::Comment(_) => Some(SemanticTokenType::Comment),
+        TokenKind::Equals | TokenKind::Assign | TokenKind::ExclMark | TokenKind::NotEqual |
+        TokenKind::Greater | TokenKind::GreaterThan | TokenKind::Less | TokenKind::LessThan |
+        TokenKind::Plus | TokenKind::Increment | TokenKind::Minus | TokenKind::Decrement |
+        TokenKind::Star | TokenKind::Multiply | TokenKind::Slash | TokenKind::Divide |
+        TokenKind::Ampersand | TokenKind::LogicalAnd | TokenKind::Vert | TokenKind::LogicalOr |
+        TokenKind::PerCent | TokenKind::QuestionTag | TokenKind::Range | TokenKind::RangeIncl |
+        TokenKind::Tilde => Some(SemanticTokenType::Operator),
+        _ => None
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SemanticToken {
+    pub line: u32,
+    pub start: u32,
+    pub length: u32,
+    pub token_type: SemanticTokenType
+}
+
+/// Returns the semantic tokens of the text in text order.
+/// Tokens spanning several lines (block comments) are split into one semantic token per line,
+/// text that could not be lexed is skipped.
+pub fn semantic_tokens(text: &str) -> Vec<SemanticToken> {
+    let mut tokens = Vec::new();
+    let mut lexer = Lexer::new_from_str_with_trivia(text);
+    let mut line = 0u32;
+    let mut column = 0u32;
+    let mut start = 0usize;
+    loop {
+        let token_type = match lexer.next() {
+            None => break,
+            Some(result) => result.ok().and_then(|t| token_type(&t.kind))
+        };
+        let end = lexer.offset();
+        for (i, piece) in text[start..end].split('\n').enumerate() {
+            if i > 0 {
+                line += 1;
+                column = 0;
+            }
+            let length = piece.trim_end_matches('\r').encode_utf16().count() as u32;
+            if let Some(token_type) = token_type {
+                if length > 0 {
+                    tokens.push(SemanticToken{line, start: column, length, token_type});
+                }
+            }
+            column += piece.encode_utf16().count() as u32;
+        }
+        start = end;
+    }
+    tokens
+}
+
+/// Encodes the tokens as LSP `SemanticTokens.data`: five integers per token holding the line
+/// delta, the start delta (relative to the previous token when on the same line), the length,
+/// the token type index and the (always empty) modifier set.
+pub fn encode(tokens: &[SemanticToken]) -> Vec<u32> {
+    let mut data = Vec::with_capacity(tokens.len() * 5);
+    let mut line = 0u32;
+    let mut start = 0u32;
+    for token in tokens {
+        let delta_line = token.line - line;
+        let delta_start = if delta_line == 0 { token.start - start } else { token.start };
+        data.extend_from_slice(&[delta_line, delta_start, token.length, token.token_type.index(), 0]);
+        line = token.line;
+        start = token.start;
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use SemanticTokenType::*;
+
+    fn token(line: u32, start: u32, length: u32, token_type: SemanticTokenType) -> SemanticToken {
+        SemanticToken{line, start, length, token_type}
+    }
+
+    #[test]
+    fn legend() {
+        for t in [Identifier, Number, Operator, Comment] {
+            assert_eq!(SemanticTokenType::LEGEND[t.index() as usize], t.name());
+        }
+    }
+
+    #[test]
+    fn tokens() {
+        let tokens = semantic_tokens("speed { max = 2.5e1 } // km/h\nlimit>=0x10");
+        assert_eq!(tokens, vec![
+            token(0, 0, 5, Identifier),
+            token(0, 8, 3, Identifier),
+            token(0, 12, 1, Operator),
+            token(0, 14, 5, Number),
+            token(0, 22, 7, Comment),
+            token(1, 0, 5, Identifier),
+            token(1, 5, 2, Operator),
+            token(1, 7, 4, Number),
+        ]);
+    }
+
+    #[test]
+    fn multiline_comment() {
+        let tokens = semantic_tokens("a /* one\r\n\r\n two */ b");
+        assert_eq!(tokens, vec![
+            token(0, 0, 1, Identifier),
+            token(0, 2, 6, Comment),
+            token(2, 0, 7, Comment),
+            token(2, 8, 1, Identifier),
+        ]);
+    }
+
+    #[test]
+    fn utf16_columns() {
+        // U+1F600 is encoded as a surrogate pair and is no valid token
+        let tokens = semantic_tokens("\u{1F600} \u{2018}x y");
+        assert_eq!(tokens, vec![
+            token(0, 4, 1, Identifier),
+            token(0, 6, 1, Identifier),
+        ]);
+    }
+
+    #[test]
+    fn encoding() {
+        let tokens = [
+            token(0, 2, 3, Identifier),
+            token(0, 8, 1, Operator),
+            token(3, 4, 2, Number),
+            token(3, 10, 5, Comment),
+        ];
+        assert_eq!(encode(&tokens), vec![
+            0, 2, 3, 0, 0,
+            0, 6, 1, 2, 0,
+            3, 4, 2, 1, 0,
+            0, 6, 5, 3, 0,
+        ]);
+    }
+}
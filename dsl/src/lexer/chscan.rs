@@ -12,6 +12,7 @@ use std::str::Chars;
 /// similar to `std::str::Chars` with additional position and a marker.
 #[derive(Clone, Debug)]
 pub struct ChScanner<'a> {
+    text: &'a str,
     iter: Chars<'a>,
     peeked: Option<(Option<char>, Chars<'a>)>,
     position: usize,
@@ -22,7 +23,15 @@ impl<'a> ChScanner<'a> {
 
     /// Creates a new initialized `ChScanner` with the given text.
     pub fn new_from_str(text: &'a str) -> Self {
-        ChScanner {iter: text.chars(), peeked: None, position: 0usize, marker: None}
+        ChScanner {text, iter: text.chars(), peeked: None, position: 0usize, marker: None}
+    }
+
+    /// Creates a new `ChScanner` for the given text that starts scanning at the byte `offset`.
+    /// `position` is the index of the UTF code point at `offset`.
+    /// # Panics
+    /// Panics when `offset` is not on a UTF code point boundary of `text`.
+    pub fn new_at(text: &'a str, offset: usize, position: usize) -> Self {
+        ChScanner {text, iter: text[offset..].chars(), peeked: None, position, marker: None}
     }
 
     /// Returns the index of the next to be scanned UTF code point.
@@ -30,6 +39,11 @@ impl<'a> ChScanner<'a> {
         self.position
     }
 
+    /// Returns the byte offset of the next to be scanned UTF code point.
+    pub fn offset(&self) -> usize {
+        self.text.len() - self.iter_peek_free().as_str().len()
+    }
+
     /// Scans the next character and returns it or `None` if the file is at its end.
     /// In contrast to `next()` this method does not consume scanned character and does not advance the position.
     /// So calling `peek()` multiple times without interleaving calls to `next()` returns the same result.
@@ -38,7 +52,7 @@ impl<'a> ChScanner<'a> {
             let iter_clone = self.iter.clone();
             self.peeked = Some(( self.iter.next(), iter_clone ))
         }
-        self.peeked.as_ref().map(|(a,_)| a).unwrap().clone()
+        *self.peeked.as_ref().map(|(a,_)| a).unwrap()
     }
 
     fn iter_peek_free(&self) -> Chars<'a> {
@@ -54,12 +68,8 @@ impl<'a> ChScanner<'a> {
         self.marker = Some((self.iter_peek_free(), self.position))
     }
 
-    /// Clears the marker - has no effect when no marker is set.
-    pub fn clr_marker(&mut self) {
-        self.marker = None
-    }
-
     /// Returns `true` when a marker is currently set - otherwise `false`.
+    #[cfg(test)]
     pub fn is_marker_set(&self) -> bool {
         self.marker.is_some()
     }
@@ -79,13 +89,13 @@ impl<'a> ChScanner<'a> {
         unsafe {
             let size = me.offset_from(ms) as usize;
             let bytes = std::ptr::slice_from_raw_parts::<u8>(ms, size).as_ref().unwrap();
-            return std::str::from_utf8_unchecked(bytes)
+            std::str::from_utf8_unchecked(bytes)
         }
     }
 
     pub fn pos_marker(&self) -> usize {
         assert!(self.marker.is_some());
-        return self.marker.as_ref().map(|(_,a)| a).unwrap().clone()
+        *self.marker.as_ref().map(|(_,a)| a).unwrap()
     }
 }
 
@@ -202,6 +212,34 @@ mod tests {
         assert_eq!(scanner.str_from_marker(), "test");
     }
 
+    #[test]
+    fn offset() {
+        let mut scanner = ChScanner::new_from_str("a\u{2018}b");
+        assert_eq!(scanner.offset(), 0);
+        scanner.next();
+        assert_eq!(scanner.offset(), 1);
+        scanner.next();
+        assert_eq!(scanner.offset(), 4);
+        assert_eq!(scanner.position(), 2);
+        scanner.peek();
+        assert_eq!(scanner.offset(), 4);
+        scanner.next();
+        assert_eq!(scanner.offset(), 5);
+    }
+
+    #[test]
+    fn start_at_offset() {
+        let mut scanner = ChScanner::new_at("a\u{2018}bc", 4, 2);
+        assert_eq!(scanner.offset(), 4);
+        assert_eq!(scanner.position(), 2);
+        scanner.set_marker();
+        assert_eq!(scanner.next(), Some('b'));
+        assert_eq!(scanner.str_from_marker(), "b");
+        assert_eq!(scanner.pos_marker(), 2);
+        assert_eq!(scanner.position(), 3);
+        assert_eq!(scanner.offset(), 5);
+    }
+
     #[test]
     fn test_marker_empty() {
         let mut scanner = ChScanner::new_from_str("A\ntest.");
@@ -7,7 +7,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 mod chscan;
-mod token;
+pub mod token;
 
 #[cfg(test)]
 mod tests;
@@ -16,10 +16,56 @@ use std::str::FromStr;
 use chscan::*;
 use token::TokenKind;
 
-///
+/// Position of a `Lexer` between two tokens, used to restart lexing without scanning the text
+/// in front of it. The lexer carries no other state from one token to the next.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LexerState {
+    offset: usize,
+    position: usize,
+    trivia: bool
+}
+
+impl LexerState {
+
+    /// Creates a state at the byte `offset` which must be the start of the UTF code point with
+    /// index `position` in the text - this is not checked.
+    pub(crate) fn new(offset: usize, position: usize, trivia: bool) -> Self {
+        LexerState{offset, position, trivia}
+    }
+
+    /// Creates a state at the byte `offset` of `text`.
+    /// # Panics
+    /// Panics when `offset` is not on a UTF code point boundary of `text`.
+    pub fn at_offset(text: &str, offset: usize, trivia: bool) -> Self {
+        assert!(text.is_char_boundary(offset));
+        LexerState{offset, position: text[..offset].chars().count(), trivia}
+    }
+
+    /// Returns the byte offset into the text.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the index of the UTF code point at `offset()`.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns `true` when whitespace and comment tokens are emitted.
+    pub fn trivia(&self) -> bool {
+        self.trivia
+    }
+}
+
+/// `Lexer` splits a text into tokens, see `token::TokenKind`.
+/// By default whitespace and comments are skipped. A lexer created with `new_from_str_with_trivia`
+/// emits them as `TokenKind::Whitespace` and `TokenKind::Comment` tokens instead, so that the
+/// tokens cover the text without gaps.
 pub struct Lexer<'a> {
     scanner: ChScanner<'a>,
-    peeked: Option< Option<token::Result<'a>>>
+    peeked: Option< Option<token::Result<'a>>>,
+    peeked_state: Option<LexerState>,
+    trivia: bool
 }
 
 impl<'a> Lexer<'a> {
@@ -28,12 +74,55 @@ impl<'a> Lexer<'a> {
     pub fn new_from_str(text: &'a str) -> Self {
         Lexer{
             scanner: ChScanner::new_from_str(text),
-            peeked: None
+            peeked: None,
+            peeked_state: None,
+            trivia: false
+        }
+    }
+
+    /// Creates a new Lexer for the given text that emits whitespace and comment tokens.
+    pub fn new_from_str_with_trivia(text: &'a str) -> Self {
+        Lexer{
+            scanner: ChScanner::new_from_str(text),
+            peeked: None,
+            peeked_state: None,
+            trivia: true
+        }
+    }
+
+    /// Creates a new Lexer for the given text that continues at a state returned by `state()` or
+    /// created with `LexerState::at_offset()` for the same text.
+    /// Token positions count from the start of the text, not from the state.
+    /// # Panics
+    /// Panics when the offset of `state` is not on a UTF code point boundary of `text`.
+    pub fn new_from_state(text: &'a str, state: LexerState) -> Self {
+        Lexer{
+            scanner: ChScanner::new_at(text, state.offset, state.position),
+            peeked: None,
+            peeked_state: None,
+            trivia: state.trivia
         }
     }
 
+    /// Returns the state after the last token returned by `next()`.
+    /// A lexer created from this state returns the same tokens as this lexer.
+    pub fn state(&self) -> LexerState {
+        self.peeked_state.unwrap_or(LexerState{
+            offset: self.scanner.offset(),
+            position: self.scanner.position(),
+            trivia: self.trivia
+        })
+    }
+
+    /// Returns the byte offset into the text up to which the lexer has scanned.
+    /// A token returned by `peek()` is already included.
+    pub fn offset(&self) -> usize {
+        self.scanner.offset()
+    }
+
     pub fn peek(&mut self) -> Option<token::Result<'a>> {
         if self.peeked.is_none() {
+            self.peeked_state = Some(self.state());
             self.peeked = Some(self.scan_token());
         }
         self.peeked.as_ref().unwrap().clone()
@@ -42,16 +131,55 @@ impl<'a> Lexer<'a> {
     fn scan_token(&mut self) -> Option<token::Result<'a>> {
         loop {
             self.scanner.set_marker();
-            match self.scanner.next() {
-                None => return None,
-                Some(ch) => {
-                    match ch {
-                        ' ' | '\r' | '\n' | '\t' => continue,
-                        _ => return self.scan_init_char(ch)
-                    }
-                }
+            let ch = self.scanner.next()?;
+            let result = match ch {
+                ' ' | '\r' | '\n' | '\t' => self.scan_whitespace(),
+                _ => self.scan_init_char(ch)
+            };
+            match result {
+                Some(Ok(token::Token{kind: TokenKind::Whitespace(_) | TokenKind::Comment(_), ..}))
+                    if !self.trivia => continue,
+                _ => return result
+            }
+        }
+    }
+
+    fn scan_whitespace(&mut self) -> Option<token::Result<'a>> {
+        while let Some(' ' | '\r' | '\n' | '\t') = self.scanner.peek() {
+            self.scanner.next();
+        }
+        Some(Ok( token::make_token(
+            self.scanner.pos_marker(),
+            TokenKind::Whitespace(self.scanner.str_from_marker())
+        )))
+    }
+
+    fn scan_line_comment(&mut self) -> Option<token::Result<'a>> {
+        // we enter here after the // is consumed, the line break is not part of the comment
+        while let Some(ch) = self.scanner.peek() {
+            if ch == '\n' {
+                break
             }
+            self.scanner.next();
         }
+        Some(Ok( token::make_token(
+            self.scanner.pos_marker(),
+            TokenKind::Comment(self.scanner.str_from_marker())
+        )))
+    }
+
+    fn scan_block_comment(&mut self) -> Option<token::Result<'a>> {
+        // we enter here after the /* is consumed, block comments do not nest
+        while let Some(ch) = self.scanner.next() {
+            if ch == '*' && self.scanner.peek() == Some('/') {
+                self.scanner.next();
+                return Some(Ok( token::make_token(
+                    self.scanner.pos_marker(),
+                    TokenKind::Comment(self.scanner.str_from_marker())
+                )))
+            }
+        }
+        Some(Err(token::Error::CommentNotTerminated(self.scanner.str_from_marker(), self.scanner.pos_marker())))
     }
 
     fn scan_init_char(&mut self, ch: char) -> Option<token::Result<'a>> {
@@ -63,7 +191,7 @@ impl<'a> Lexer<'a> {
             '=' => {
                 match self.scanner.peek() {
                     Some('=') => {
-                        self.next();
+                        self.scanner.next();
                         Some(Ok( token::make_token(self.scanner.position() - 2, TokenKind::Equals)))
                     }
                     _ => Some(Ok( token::make_token(self.scanner.position() - 1, TokenKind::Assign)))
@@ -72,7 +200,7 @@ impl<'a> Lexer<'a> {
             '!' => {
                 match self.scanner.peek() {
                     Some('=') => {
-                        self.next();
+                        self.scanner.next();
                         Some(Ok( token::make_token(self.scanner.position() - 2, TokenKind::NotEqual)))
                     }
                     _ => Some(Ok( token::make_token(self.scanner.position() - 1, TokenKind::ExclMark)))
@@ -81,7 +209,7 @@ impl<'a> Lexer<'a> {
             '>' => {
                 match self.scanner.peek() {
                     Some('=') => {
-                        self.next();
+                        self.scanner.next();
                         Some(Ok( token::make_token(self.scanner.position() - 2, TokenKind::GreaterThan)))
                     }
                     _ => Some(Ok( token::make_token(self.scanner.position() - 1, TokenKind::Greater)))
@@ -90,7 +218,7 @@ impl<'a> Lexer<'a> {
             '<' => {
                 match self.scanner.peek() {
                     Some('=') => {
-                        self.next();
+                        self.scanner.next();
                         Some(Ok( token::make_token(self.scanner.position() - 2, TokenKind::LessThan)))
                     }
                     _ => Some(Ok( token::make_token(self.scanner.position() - 1, TokenKind::Less)))
@@ -99,7 +227,7 @@ impl<'a> Lexer<'a> {
             '+' => {
                 match self.scanner.peek() {
                     Some('=') => {
-                        self.next();
+                        self.scanner.next();
                         Some(Ok( token::make_token(self.scanner.position() - 2, TokenKind::Increment)))
                     }
                     _ => Some(Ok( token::make_token(self.scanner.position() - 1, TokenKind::Plus)))
@@ -108,7 +236,7 @@ impl<'a> Lexer<'a> {
             '-' => {
                 match self.scanner.peek() {
                     Some('=') => {
-                        self.next();
+                        self.scanner.next();
                         Some(Ok( token::make_token(self.scanner.position() - 2, TokenKind::Decrement)))
                     }
                     _ => Some(Ok( token::make_token(self.scanner.position() - 1, TokenKind::Minus)))
@@ -117,7 +245,7 @@ impl<'a> Lexer<'a> {
             '*' => {
                 match self.scanner.peek() {
                     Some('=') => {
-                        self.next();
+                        self.scanner.next();
                         Some(Ok( token::make_token(self.scanner.position() - 2, TokenKind::Multiply)))
                     }
                     _ => Some(Ok( token::make_token(self.scanner.position() - 1, TokenKind::Star)))
@@ -125,8 +253,16 @@ impl<'a> Lexer<'a> {
             },
             '/' => {
                 match self.scanner.peek() {
+                    Some('/') => {
+                        self.scanner.next();
+                        self.scan_line_comment()
+                    }
+                    Some('*') => {
+                        self.scanner.next();
+                        self.scan_block_comment()
+                    }
                     Some('=') => {
-                        self.next();
+                        self.scanner.next();
                         Some(Ok( token::make_token(self.scanner.position() - 2, TokenKind::Divide)))
                     }
                     _ => Some(Ok( token::make_token(self.scanner.position() - 1, TokenKind::Slash)))
@@ -135,7 +271,7 @@ impl<'a> Lexer<'a> {
             '&' => {
                 match self.scanner.peek() {
                     Some('&') => {
-                        self.next();
+                        self.scanner.next();
                         Some(Ok( token::make_token(self.scanner.position() - 2, TokenKind::LogicalAnd)))
                     }
                     _ => Some(Ok( token::make_token(self.scanner.position() - 1, TokenKind::Ampersand)))
@@ -144,7 +280,7 @@ impl<'a> Lexer<'a> {
             '|' => {
                 match self.scanner.peek() {
                     Some('|') => {
-                        self.next();
+                        self.scanner.next();
                         Some(Ok( token::make_token(self.scanner.position() - 2, TokenKind::LogicalOr)))
                     }
                     _ => Some(Ok( token::make_token(self.scanner.position() - 1, TokenKind::Vert)))
@@ -157,7 +293,7 @@ impl<'a> Lexer<'a> {
             '\'' =>  {
                 match self.scanner.peek() {
                     Some('\'') => {
-                        self.next();
+                        self.scanner.next();
                         Some(Ok( token::make_token(self.scanner.position() - 2, TokenKind::SingleQuote2)))
                     }
                     _ => Some(Ok( token::make_token(self.scanner.position() - 1, TokenKind::SingleQuote)))
@@ -168,10 +304,10 @@ impl<'a> Lexer<'a> {
             '.' => {
                 match self.scanner.peek() {
                     Some('.') => {
-                        self.next();
+                        self.scanner.next();
                         match self.scanner.peek() {
                             Some('=') => {
-                                self.next();
+                                self.scanner.next();
                                 Some(Ok( token::make_token(self.scanner.position() - 3, TokenKind::RangeIncl)))
                             }
                             _ => Some(Ok( token::make_token(self.scanner.position() - 2, TokenKind::Range)))
@@ -184,7 +320,7 @@ impl<'a> Lexer<'a> {
             '[' =>  {
                 match self.scanner.peek() {
                     Some('[') => {
-                        self.next();
+                        self.scanner.next();
                         Some(Ok( token::make_token(self.scanner.position() - 2, TokenKind::DoubleBracketLeft)))
                     }
                     _ => Some(Ok( token::make_token(self.scanner.position() - 1, TokenKind::BracketLeft)))
@@ -193,7 +329,7 @@ impl<'a> Lexer<'a> {
             ']' =>  {
                 match self.scanner.peek() {
                     Some(']') => {
-                        self.next();
+                        self.scanner.next();
                         Some(Ok( token::make_token(self.scanner.position() - 2, TokenKind::DoubleBracketRight)))
                     }
                     _ => Some(Ok( token::make_token(self.scanner.position() - 1, TokenKind::BracketRight)))
@@ -209,7 +345,7 @@ impl<'a> Lexer<'a> {
                 }
             }
             '1'..='9' => self.scan_dec(ch),
-            _ => Some(Err(token::Error::Internal))
+            _ => Some(Err(token::Error::UnknownToken(self.scanner.str_from_marker())))
         }
     }
 
@@ -241,10 +377,10 @@ impl<'a> Lexer<'a> {
             break
         }
         let string = self.scanner.str_from_marker();
-        return if string.ends_with('\'') {
+        if string.ends_with('\'') {
             Some(Err(token::Error::IntegerSeparatorAtEnd(string, self.scanner.pos_marker())))
         } else {
-            match u64::from_str_radix(&buf, 10) {
+            match buf.parse::<u64>() {
                 Ok(v) => Some(Ok(token::make_token(
                     self.scanner.pos_marker(),
                     TokenKind::Integer(self.scanner.str_from_marker(), v)
@@ -267,16 +403,13 @@ impl<'a> Lexer<'a> {
                         self.scanner.next();
                         continue
                     }
-                    'e' | 'E' => {
-                        if !in_exp {
-                            in_exp = true;
+                    'e' | 'E' if !in_exp => {
+                        in_exp = true;
+                        self.scanner.next();
+                        if let Some('-') | Some('+') = self.scanner.peek() {
                             self.scanner.next();
-                            match self.scanner.peek() {
-                                Some('-') | Some('+') => {self.scanner.next();},
-                                _ => {}
-                            }
-                            continue
                         }
+                        continue
                     }
                     _ => {}
                 }
@@ -284,13 +417,13 @@ impl<'a> Lexer<'a> {
             break
         }
         let string = self.scanner.str_from_marker();
-        return match f64::from_str(string) {
+        match f64::from_str(string) {
             Ok(v) => Some(Ok( token::make_token(
                 self.scanner.pos_marker(),
                 TokenKind::Float(string, v)
             ))),
             Err(_) => Some(Err(
-                token::Error::FloatParsingError(string, self.scanner.pos_marker())
+                token::Error::FloatParsingFailed(string, self.scanner.pos_marker())
             ))
         }
     }
@@ -370,18 +503,8 @@ impl<'a> Lexer<'a> {
     }
 
     fn scan_identifier(&mut self) -> Option<token::Result<'a>> {
-        loop {
-            if let Some(ch) = self.scanner.peek() {
-                match ch {
-                    'a'..='z' | 'A'..='Z' | '_' | '0'..='9' => {
-                        self.scanner.next();
-                        continue
-                    },
-                    _ => break
-                }
-            } else {
-                break
-            }
+        while let Some('a'..='z' | 'A'..='Z' | '_' | '0'..='9') = self.scanner.peek() {
+            self.scanner.next();
         }
         Some( Ok(
             token::make_token(self.scanner.pos_marker(),
@@ -395,6 +518,7 @@ impl<'a> Iterator for Lexer<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(p) = self.peeked.take() {
+            self.peeked_state = None;
             p
         } else {
             self.scan_token()
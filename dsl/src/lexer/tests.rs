@@ -293,3 +293,114 @@ fn float() {
     assert_token_float!(lexer.next(), 17, "0.34e-4", 0.34e-4);
     assert_token_float!(lexer.next(), 25, "1.22e+2", 1.22e2);
 }
+
+#[test]
+fn double_char_operators_followed_by_same_char() {
+    use token::TokenKind::*;
+    let mut lexer = Lexer::new_from_str("=== !== ..=.");
+
+    assert_token!(lexer.next(), 0, Equals);
+    assert_token!(lexer.next(), 2, Assign);
+    assert_token!(lexer.next(), 4, NotEqual);
+    assert_token!(lexer.next(), 6, Assign);
+    assert_token!(lexer.next(), 8, RangeIncl);
+    assert_token!(lexer.next(), 11, Dot);
+    assert!(lexer.next().is_none());
+}
+
+#[test]
+fn comments_skipped() {
+    use token::TokenKind::*;
+    let mut lexer = Lexer::new_from_str("a // line comment\nb /* block\n comment */ c/**/d / e");
+
+    assert_token!(lexer.next(), 0, Identifier("a"));
+    assert_token!(lexer.next(), 18, Identifier("b"));
+    assert_token!(lexer.next(), 41, Identifier("c"));
+    assert_token!(lexer.next(), 46, Identifier("d"));
+    assert_token!(lexer.next(), 48, Slash);
+    assert_token!(lexer.next(), 50, Identifier("e"));
+    assert!(lexer.next().is_none());
+}
+
+#[test]
+fn comment_not_terminated() {
+    let mut lexer = Lexer::new_from_str("a /* comment * /");
+
+    assert_token!(lexer.next(), 0, token::TokenKind::Identifier("a"));
+    assert_eq!(lexer.next().unwrap().unwrap_err(), token::Error::CommentNotTerminated("/* comment * /", 2));
+    assert!(lexer.next().is_none());
+}
+
+#[test]
+fn trivia() {
+    use token::TokenKind::*;
+    let mut lexer = Lexer::new_from_str_with_trivia(" a\t// c\n\r\n/* d */b");
+
+    assert_token!(lexer.next(), 0, Whitespace(" "));
+    assert_token!(lexer.next(), 1, Identifier("a"));
+    assert_token!(lexer.next(), 2, Whitespace("\t"));
+    assert_token!(lexer.next(), 3, Comment("// c"));
+    assert_token!(lexer.next(), 7, Whitespace("\n\r\n"));
+    assert_token!(lexer.next(), 10, Comment("/* d */"));
+    assert_token!(lexer.next(), 17, Identifier("b"));
+    assert!(lexer.next().is_none());
+}
+
+#[test]
+fn offset() {
+    let mut lexer = Lexer::new_from_str_with_trivia("\u{2018}b c");
+
+    assert_eq!(lexer.offset(), 0);
+    assert!(lexer.next().unwrap().is_err());
+    assert_eq!(lexer.offset(), 3);
+    lexer.peek();
+    assert_eq!(lexer.offset(), 4);
+    lexer.next();
+    assert_eq!(lexer.offset(), 4);
+}
+
+#[test]
+fn unknown_token() {
+    let mut lexer = Lexer::new_from_str("a @\u{2018}");
+
+    assert_token!(lexer.next(), 0, token::TokenKind::Identifier("a"));
+    assert_eq!(lexer.next().unwrap().unwrap_err(), token::Error::UnknownToken("@"));
+    assert_eq!(lexer.next().unwrap().unwrap_err(), token::Error::UnknownToken("\u{2018}"));
+    assert!(lexer.next().is_none());
+}
+
+#[test]
+fn restart_from_state() {
+    let text = "a { b = 0x1f } // c\n\u{2018} 2.5";
+    let full: Vec<_> = Lexer::new_from_str_with_trivia(text).collect();
+
+    let mut lexer = Lexer::new_from_str_with_trivia(text);
+    let mut tokens = Vec::new();
+    for _ in 0..full.len() {
+        // restart after every token, peeking must not change the saved state
+        lexer.peek();
+        let state = lexer.state();
+        lexer = Lexer::new_from_state(text, state);
+        tokens.push(lexer.next().unwrap());
+    }
+    assert!(lexer.next().is_none());
+    assert_eq!(format!("{:?}", tokens), format!("{:?}", full));
+}
+
+#[test]
+fn restart_at_offset() {
+    use crate::lexer::LexerState;
+    use token::TokenKind::*;
+    let text = "\u{2018}ab cd /* e */ f";
+
+    let mut lexer = Lexer::new_from_state(text, LexerState::at_offset(text, 7, false));
+    assert_token!(lexer.next(), 5, Identifier("d"));
+    assert_token!(lexer.next(), 15, Identifier("f"));
+    assert!(lexer.next().is_none());
+
+    let state = LexerState::at_offset(text, 5, true);
+    assert_eq!((state.offset(), state.position(), state.trivia()), (5, 3, true));
+    let mut lexer = Lexer::new_from_state(text, state);
+    assert_token!(lexer.next(), 3, Whitespace(" "));
+    assert_eq!(lexer.state(), LexerState::new(6, 4, true));
+}
@@ -6,6 +6,8 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
+use std::fmt;
+
 pub type Result<'a> = std::result::Result<Token<'a>, Error<'a>>;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -13,12 +15,27 @@ pub enum Error<'a> {
     IntegerNoValue(&'a str, usize),
     IntegerSeparatorAtEnd(&'a str, usize),
     IntegerExceedingLimit(&'a str, usize),
-    FloatParsingError(&'a str, usize),
+    FloatParsingFailed(&'a str, usize),
+    CommentNotTerminated(&'a str, usize),
 
     UnknownToken(&'a str),
     Internal
 }
 
+impl fmt::Display for Error<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::IntegerNoValue(s, _) => write!(f, "integer literal `{}` has no digits", s),
+            Error::IntegerSeparatorAtEnd(s, _) => write!(f, "integer literal `{}` ends with a digit separator", s),
+            Error::IntegerExceedingLimit(s, _) => write!(f, "integer literal `{}` exceeds 64 bits", s),
+            Error::FloatParsingFailed(s, _) => write!(f, "invalid float literal `{}`", s),
+            Error::CommentNotTerminated(_, _) => write!(f, "block comment is not terminated"),
+            Error::UnknownToken(s) => write!(f, "unknown token `{}`", s),
+            Error::Internal => write!(f, "internal lexer error")
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Token<'a> {
     pub position: usize,
@@ -34,6 +51,8 @@ pub enum TokenKind<'a> {
     Identifier(&'a str),
     Integer(&'a str, u64),
     Float(&'a str, f64),
+    Whitespace(&'a str),
+    Comment(&'a str),
 
     BracesLeft,         // {
     BracesRight,        // }
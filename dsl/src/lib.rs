@@ -6,5 +6,10 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
-mod lexer;
+pub mod lexer;
+pub mod cst;
+pub mod highlight;
+pub mod config;
+pub mod diagnostic;
+pub mod query;
 mod parser;
\ No newline at end of file
@@ -0,0 +1,260 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+ * Copyright (C) 2023 Alexander Seifarth
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! Demand-driven, memoised computation over the files of a workspace.
+//!
+//! The inputs of the `Database` are the file texts. Every change of an input starts a new revision.
+//! Derived values (syntax tree, diagnostics) are computed when they are asked for and memoised
+//! together with the revision in which they were last verified and the revision in which their
+//! value last changed. A memo is reused as long as none of its dependencies changed after it was
+//! verified. When a recomputed value equals the memoised one, its change revision is kept, so
+//! values depending on it are not recomputed either (early cutoff).
+//!
+//! Queries:
+//! * `file_text` (input) -> `syntax` -> `diagnostics`
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use crate::cst::{self, GreenNode, SyntaxNode, TextEdit};
+use crate::diagnostic::{self, Diagnostic};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(usize);
+
+struct FileInput {
+    text: Rc<str>,
+    changed_at: u64,
+    // edit leading from the text of the memoised syntax tree to `text`, used for incremental reparsing
+    edit: Option<TextEdit>
+}
+
+struct Memo<T> {
+    value: T,
+    verified_at: u64,
+    changed_at: u64
+}
+
+#[derive(Default)]
+pub struct Database {
+    revision: u64,
+    paths: Vec<PathBuf>,
+    ids: HashMap<PathBuf, FileId>,
+    inputs: HashMap<FileId, FileInput>,
+    syntax: RefCell<HashMap<FileId, Memo<Rc<GreenNode>>>>,
+    diagnostics: RefCell<HashMap<FileId, Memo<Rc<[Diagnostic]>>>>
+}
+
+impl Database {
+
+    pub fn new() -> Self {
+        Database::default()
+    }
+
+    /// Returns the current revision, it is incremented by every input change.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Returns the id of the file with the given path - `None` if the file was never added.
+    pub fn file_id(&self, path: &Path) -> Option<FileId> {
+        self.ids.get(path).copied()
+    }
+
+    pub fn path(&self, file: FileId) -> &Path {
+        &self.paths[file.0]
+    }
+
+    /// Returns the files currently in the database ordered by path.
+    pub fn files(&self) -> Vec<FileId> {
+        let mut files: Vec<FileId> = self.inputs.keys().copied().collect();
+        files.sort_by(|a, b| self.path(*a).cmp(self.path(*b)));
+        files
+    }
+
+    /// Sets the text of a file, adding the file if it is not yet in the database.
+    /// Setting the current text again does not start a new revision.
+    pub fn set_file_text(&mut self, path: &Path, text: &str) -> FileId {
+        let file = match self.file_id(path) {
+            Some(file) => file,
+            None => {
+                let file = FileId(self.paths.len());
+                self.paths.push(path.to_path_buf());
+                self.ids.insert(path.to_path_buf(), file);
+                file
+            }
+        };
+        if self.inputs.get(&file).is_some_and(|input| &*input.text == text) {
+            return file
+        }
+        self.revision += 1;
+        self.inputs.insert(file, FileInput{text: text.into(), changed_at: self.revision, edit: None});
+        file
+    }
+
+    /// Applies an edit to the text of a file. When the syntax tree of the previous text was
+    /// computed, the next `syntax()` query reparses it incrementally.
+    /// # Panics
+    /// Panics when the file is not in the database or the edit range is outside of its text.
+    pub fn edit_file(&mut self, file: FileId, edit: &TextEdit) {
+        let text: Rc<str> = edit.apply(&self.inputs[&file].text).into();
+        let current = self.syntax.borrow().get(&file)
+            .is_some_and(|memo| memo.verified_at >= self.inputs[&file].changed_at);
+        self.revision += 1;
+        self.inputs.insert(file, FileInput{
+            text,
+            changed_at: self.revision,
+            edit: if current { Some(edit.clone()) } else { None }
+        });
+    }
+
+    /// Removes a file and everything computed for it.
+    pub fn remove_file(&mut self, file: FileId) {
+        if self.inputs.remove(&file).is_some() {
+            self.revision += 1;
+            self.syntax.borrow_mut().remove(&file);
+            self.diagnostics.borrow_mut().remove(&file);
+        }
+    }
+
+    /// Returns the text of a file.
+    /// # Panics
+    /// Panics when the file is not in the database - this holds for all queries.
+    pub fn file_text(&self, file: FileId) -> Rc<str> {
+        self.inputs[&file].text.clone()
+    }
+
+    /// Returns the green syntax tree of a file.
+    pub fn syntax(&self, file: FileId) -> Rc<GreenNode> {
+        let input = &self.inputs[&file];
+        let mut memos = self.syntax.borrow_mut();
+        if let Some(memo) = memos.get_mut(&file) {
+            if memo.verified_at >= input.changed_at {
+                memo.verified_at = self.revision;
+                return memo.value.clone()
+            }
+        }
+        let old = memos.remove(&file);
+        let value = match (&old, &input.edit) {
+            (Some(memo), Some(edit)) => cst::reparse(&SyntaxNode::new_root(memo.value.clone()), &input.text, edit),
+            _ => cst::build_green(&input.text)
+        };
+        self.store(&mut memos, file, old, value)
+    }
+
+    /// Returns the diagnostics of a file in text order.
+    pub fn diagnostics(&self, file: FileId) -> Rc<[Diagnostic]> {
+        let syntax = self.syntax(file);
+        let syntax_changed_at = self.syntax.borrow()[&file].changed_at;
+        let mut memos = self.diagnostics.borrow_mut();
+        if let Some(memo) = memos.get_mut(&file) {
+            if memo.verified_at >= syntax_changed_at {
+                memo.verified_at = self.revision;
+                return memo.value.clone()
+            }
+        }
+        let old = memos.remove(&file);
+        let value = diagnostic::check_syntax(&SyntaxNode::new_root(syntax)).into();
+        self.store(&mut memos, file, old, value)
+    }
+
+    fn store<T: PartialEq + Clone>(&self, memos: &mut HashMap<FileId, Memo<T>>, file: FileId, old: Option<Memo<T>>, value: T) -> T {
+        let memo = match old {
+            Some(old) if old.value == value => Memo{value: old.value, verified_at: self.revision, changed_at: old.changed_at},
+            _ => Memo{value, verified_at: self.revision, changed_at: self.revision}
+        };
+        let value = memo.value.clone();
+        memos.insert(file, memo);
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files() {
+        let mut db = Database::new();
+        let b = db.set_file_text(Path::new("b.ram"), "b");
+        let a = db.set_file_text(Path::new("a.ram"), "a");
+        assert_eq!(db.revision(), 2);
+        assert_eq!(db.set_file_text(Path::new("b.ram"), "b"), b);
+        assert_eq!(db.revision(), 2);
+        assert_eq!(db.files(), vec![a, b]);
+        assert_eq!(db.file_id(Path::new("a.ram")), Some(a));
+        assert_eq!(db.path(b), Path::new("b.ram"));
+
+        db.remove_file(a);
+        assert_eq!(db.files(), vec![b]);
+        assert_eq!(db.revision(), 3);
+    }
+
+    #[test]
+    fn memoised() {
+        let mut db = Database::new();
+        let a = db.set_file_text(Path::new("a.ram"), "a { @ }");
+        let b = db.set_file_text(Path::new("b.ram"), "b");
+        let syntax = db.syntax(a);
+        let diagnostics = db.diagnostics(a);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(Rc::ptr_eq(&syntax, &db.syntax(a)));
+        assert!(Rc::ptr_eq(&diagnostics, &db.diagnostics(a)));
+
+        // a change of b does not touch a
+        db.set_file_text(Path::new("b.ram"), "c");
+        assert!(Rc::ptr_eq(&syntax, &db.syntax(a)));
+        assert!(Rc::ptr_eq(&diagnostics, &db.diagnostics(a)));
+        assert_eq!(db.syntax(b).to_string(), "c");
+
+        // same syntax tree again -> diagnostics are verified without being recomputed
+        db.set_file_text(Path::new("a.ram"), "x");
+        db.set_file_text(Path::new("a.ram"), "a { @ }");
+        assert!(Rc::ptr_eq(&diagnostics, &db.diagnostics(a)));
+    }
+
+    #[test]
+    fn early_cutoff() {
+        let mut db = Database::new();
+        let a = db.set_file_text(Path::new("a.ram"), "a { @ }");
+        let diagnostics = db.diagnostics(a);
+
+        // new syntax tree, but the same diagnostics
+        db.set_file_text(Path::new("a.ram"), "b { @ }");
+        let changed_at = db.diagnostics.borrow()[&a].changed_at;
+        assert!(Rc::ptr_eq(&diagnostics, &db.diagnostics(a)));
+        assert_eq!(db.diagnostics.borrow()[&a].changed_at, changed_at);
+
+        db.set_file_text(Path::new("a.ram"), "b { 0x }");
+        assert_eq!(db.diagnostics(a)[0].message, "integer literal `0x` has no digits");
+        assert_eq!(db.diagnostics.borrow()[&a].changed_at, db.revision());
+    }
+
+    #[test]
+    fn incremental_edits() {
+        let mut db = Database::new();
+        let a = db.set_file_text(Path::new("a.ram"), "a { b } c { d }");
+        let old = db.syntax(a);
+
+        db.edit_file(a, &TextEdit::new(4..5, "@"));
+        assert_eq!(&*db.file_text(a), "a { @ } c { d }");
+        let new = db.syntax(a);
+        assert_eq!(new, cst::build_green("a { @ } c { d }"));
+        // the second group is shared with the old tree
+        assert!(Rc::ptr_eq(
+            &old.children()[6].clone().into_node().unwrap(),
+            &new.children()[6].clone().into_node().unwrap()
+        ));
+        assert_eq!(db.diagnostics(a)[0].range, 4..5);
+
+        // two edits without a query in between are parsed from scratch
+        db.edit_file(a, &TextEdit::new(0..1, "xy"));
+        db.edit_file(a, &TextEdit::new(0..0, "{"));
+        assert_eq!(db.syntax(a), cst::build_green("{xy { @ } c { d }"));
+    }
+}
@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+ * Copyright (C) 2023 Alexander Seifarth
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use dsl::config::{self, Config};
+use dsl::diagnostic::{Diagnostic, LineIndex, Severity};
+use dsl::query::Database;
+use crate::{json, usage_error, watch, workspace, EXIT_USAGE};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Human,
+    Json
+}
+
+struct FileReport {
+    path: PathBuf,
+    diagnostics: Vec<Located>
+}
+
+impl FileReport {
+    fn new(path: &Path, text: &str, diagnostics: &[Diagnostic]) -> Self {
+        let index = LineIndex::new(text);
+        let one_based = |position| {
+            let (line, column) = index.line_column(position);
+            (line + 1, column + 1)
+        };
+        let diagnostics = diagnostics.iter()
+            .map(|diagnostic| Located{
+                start: one_based(diagnostic.range.start),
+                end: one_based(diagnostic.range.end),
+                diagnostic: diagnostic.clone()
+            })
+            .collect();
+        FileReport{path: path.to_path_buf(), diagnostics}
+    }
+}
+
+/// Diagnostic with one based line and column numbers of its start and end.
+struct Located {
+    diagnostic: Diagnostic,
+    start: (usize, usize),
+    end: (usize, usize)
+}
+
+/// Runs `ram check` with the arguments following the command.
+pub fn run(args: &[String]) -> ExitCode {
+    let mut format = Format::Human;
+    let mut watch = false;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = match arg.as_str() {
+            "--watch" => {
+                watch = true;
+                continue
+            }
+            "--format" => match args.next() {
+                Some(value) => value.as_str(),
+                None => return usage_error("option `--format` requires a value")
+            },
+            _ => match arg.strip_prefix("--format=") {
+                Some(value) => value,
+                None if arg.starts_with('-') => return usage_error(&format!("unknown option `{}`", arg)),
+                None => {
+                    paths.push(PathBuf::from(arg));
+                    continue
+                }
+            }
+        };
+        format = match value {
+            "human" => Format::Human,
+            "json" => Format::Json,
+            _ => return usage_error(&format!("unknown format `{}`", value))
+        };
+    }
+    if paths.is_empty() {
+        match source_roots(format) {
+            Ok(roots) => paths = roots,
+            Err(code) => return code
+        }
+    }
+
+    if watch {
+        return watch::run(&paths, format)
+    }
+    let mut db = Database::new();
+    if let Err(message) = load(&mut db, &paths) {
+        eprintln!("ram: {}", message);
+        return ExitCode::from(EXIT_USAGE)
+    }
+    if report(&db, format) { ExitCode::FAILURE } else { ExitCode::SUCCESS }
+}
+
+/// Returns the source roots of the workspace configuration in the current directory or its
+/// ancestors - the current directory if there is none.
+fn source_roots(format: Format) -> Result<Vec<PathBuf>, ExitCode> {
+    let cwd = env::current_dir().map_err(|e| {
+        eprintln!("ram: {}", e);
+        ExitCode::from(EXIT_USAGE)
+    })?;
+    let path = match Config::find(&cwd) {
+        // keep the paths in the output short when the configuration is in the current directory
+        Some(path) if path.parent() == Some(cwd.as_path()) => PathBuf::from(config::FILE_NAME),
+        Some(path) => path,
+        None => return Ok(vec![PathBuf::from(".")])
+    };
+    match Config::load(&path) {
+        Ok(config) => Ok(config.source_roots),
+        Err(config::Error::Invalid{path, text, diagnostics}) => {
+            print(&[FileReport::new(&path, &text, &diagnostics)], format);
+            Err(ExitCode::from(EXIT_USAGE))
+        }
+        Err(e) => {
+            eprintln!("ram: {}", e);
+            Err(ExitCode::from(EXIT_USAGE))
+        }
+    }
+}
+
+/// Adds all `.ram` files in the given paths to the database.
+pub fn load(db: &mut Database, paths: &[PathBuf]) -> Result<(), String> {
+    for path in workspace::discover(paths).map_err(|e| e.to_string())? {
+        load_file(db, &path)?;
+    }
+    Ok(())
+}
+
+/// Reads a file into the database.
+pub fn load_file(db: &mut Database, path: &Path) -> Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    db.set_file_text(path, &text);
+    Ok(())
+}
+
+/// Prints the diagnostics of all files in the database and returns `true` when there are errors.
+pub fn report(db: &Database, format: Format) -> bool {
+    let reports: Vec<FileReport> = db.files().into_iter()
+        .map(|file| FileReport::new(db.path(file), &db.file_text(file), &db.diagnostics(file)))
+        .collect();
+    print(&reports, format);
+    reports.iter()
+        .flat_map(|r| r.diagnostics.iter())
+        .any(|l| l.diagnostic.severity == Severity::Error)
+}
+
+fn print(reports: &[FileReport], format: Format) {
+    match format {
+        Format::Human => print_human(reports),
+        Format::Json => print_json(reports)
+    }
+}
+
+fn print_human(reports: &[FileReport]) {
+    let mut count = 0usize;
+    for report in reports {
+        for l in &report.diagnostics {
+            println!("{}:{}:{}: {}: {}", report.path.display(), l.start.0, l.start.1,
+                     l.diagnostic.severity, l.diagnostic.message);
+            count += 1;
+        }
+    }
+    if count > 0 {
+        let files = reports.iter().filter(|r| !r.diagnostics.is_empty()).count();
+        eprintln!("ram: {} problem(s) found in {} file(s)", count, files);
+    }
+}
+
+fn print_json(reports: &[FileReport]) {
+    let entries: Vec<String> = reports.iter()
+        .flat_map(|report| report.diagnostics.iter().map(move |l| format!(
+            "{{\"file\":{},\"line\":{},\"column\":{},\"end_line\":{},\"end_column\":{},\"severity\":{},\"message\":{}}}",
+            json::string(&report.path.display().to_string()), l.start.0, l.start.1, l.end.0, l.end.1,
+            json::string(&l.diagnostic.severity.to_string()), json::string(&l.diagnostic.message)
+        )))
+        .collect();
+    println!("[{}]", entries.join(","));
+}
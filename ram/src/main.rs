@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+ * Copyright (C) 2023 Alexander Seifarth
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+mod check;
+mod json;
+mod watch;
+mod workspace;
+
+use std::env;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: ram <command> [options]
+
+Commands:
+  check [--format human|json] [--watch] [PATH...]
+        Checks all .ram files in the given files and directories and prints the diagnostics.
+        Without paths the source roots of the ram.toml in the current directory or its
+        ancestors are checked, or the current directory if there is none.
+        Exits with 1 when errors were found.
+        With --watch the check is repeated whenever a file changes.
+  help  Prints this message.
+";
+
+/// Exit code for invalid command lines and I/O failures.
+const EXIT_USAGE: u8 = 2;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("check") => check::run(&args[1..]),
+        Some("help") | Some("-h") | Some("--help") => {
+            print!("{}", USAGE);
+            ExitCode::SUCCESS
+        }
+        Some(command) => usage_error(&format!("unknown command `{}`", command)),
+        None => usage_error("no command given")
+    }
+}
+
+fn usage_error(message: &str) -> ExitCode {
+    eprintln!("ram: {}\n\n{}", message, USAGE);
+    ExitCode::from(EXIT_USAGE)
+}
@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+ * Copyright (C) 2023 Alexander Seifarth
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+//! `ram check --watch`: checks the workspace again whenever one of its files changes.
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::mpsc;
+use std::time::Duration;
+use notify::{RecursiveMode, Watcher};
+use dsl::query::Database;
+use crate::check::{self, Format};
+use crate::{workspace, EXIT_USAGE};
+
+/// Time to wait for further events after a change, editors often write a file in several steps.
+const SETTLE_TIME: Duration = Duration::from_millis(100);
+
+/// Checks the given paths, prints the diagnostics and prints them again after every change.
+/// Only returns when watching fails.
+pub fn run(paths: &[PathBuf], format: Format) -> ExitCode {
+    match watch(paths, format) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("ram: {}", message);
+            ExitCode::from(EXIT_USAGE)
+        }
+    }
+}
+
+fn watch(paths: &[PathBuf], format: Format) -> Result<(), String> {
+    // events carry absolute paths, the database uses the paths as given on the command line
+    let roots = roots(paths).map_err(|e| e.to_string())?;
+
+    // start watching first so that no change after the initial check is missed
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(|e| e.to_string())?;
+    // a directory watched recursively stays recursive only when it is not watched again afterwards
+    for root in roots.iter().filter(|r| r.file).chain(roots.iter().filter(|r| !r.file)) {
+        let (path, mode) = if root.file {
+            // the parent of a bare file name is the empty path
+            let parent = root.path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+            (parent, RecursiveMode::NonRecursive)
+        } else {
+            (root.path.as_path(), RecursiveMode::Recursive)
+        };
+        watcher.watch(path, mode).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+
+    let mut db = Database::new();
+    check::load(&mut db, paths)?;
+    report(&db, format);
+
+    while let Ok(event) = receiver.recv() {
+        let mut changed = Vec::new();
+        let mut next = Some(event);
+        while let Some(event) = next {
+            match event {
+                Ok(event) => changed.extend(event.paths.iter().filter_map(|path| relative(&roots, path))),
+                Err(e) => eprintln!("ram: {}", e)
+            }
+            next = receiver.recv_timeout(SETTLE_TIME).ok();
+        }
+        let revision = db.revision();
+        changed.sort();
+        changed.dedup();
+        for path in changed {
+            update(&mut db, &roots, &path);
+        }
+        if db.revision() != revision {
+            report(&db, format);
+        }
+    }
+    Err("file watcher stopped".to_string())
+}
+
+/// A path to check as given on the command line or in the workspace configuration.
+struct Root {
+    path: PathBuf,
+    /// Absolute forms events may report for `path`: notify joins a relative watched path to the
+    /// current directory without resolving `..` or symbolic links, some platforms report
+    /// canonical paths instead.
+    absolute: [PathBuf; 2],
+    /// A file is watched through its parent directory, so the watch survives editors saving
+    /// the file by renaming a new file over it.
+    file: bool
+}
+
+fn roots(paths: &[PathBuf]) -> io::Result<Vec<Root>> {
+    let current_dir = env::current_dir()?;
+    paths.iter()
+        .map(|path| Ok(Root{
+            path: path.clone(),
+            absolute: [current_dir.join(path), path.canonicalize()?],
+            file: !path.is_dir()
+        }))
+        .collect()
+}
+
+/// Maps an event path back to the root it is located in - `None` for the siblings of file roots.
+fn relative(roots: &[Root], path: &Path) -> Option<PathBuf> {
+    roots.iter().find_map(|root| {
+        let rest = root.absolute.iter().find_map(|a| path.strip_prefix(a).ok())?;
+        if rest.as_os_str().is_empty() {
+            Some(root.path.clone())
+        } else if root.file {
+            None
+        } else {
+            Some(root.path.join(rest))
+        }
+    })
+}
+
+/// Brings the database in line with the file system at `path`.
+/// Like `workspace::discover` files given as roots are checked regardless of their extension.
+fn update(db: &mut Database, roots: &[Root], path: &Path) {
+    match fs::symlink_metadata(path) {
+        // like `workspace::discover` symbolic links to directories are not followed
+        Ok(metadata) if metadata.is_dir() => match workspace::discover(&[path]) {
+            Ok(files) => files.iter().for_each(|file| update(db, roots, file)),
+            Err(e) => eprintln!("ram: {}", e)
+        },
+        Ok(_) => {
+            let relevant = path.extension().is_some_and(|e| e == workspace::EXTENSION)
+                || roots.iter().any(|root| root.file && root.path == path);
+            if relevant && path.is_file() {
+                if let Err(message) = check::load_file(db, path) {
+                    eprintln!("ram: {}", message);
+                }
+            }
+        }
+        Err(_) => {
+            // removed - either a file or a whole directory
+            let removed: Vec<_> = db.files().into_iter().filter(|file| db.path(*file).starts_with(path)).collect();
+            removed.into_iter().for_each(|file| db.remove_file(file));
+        }
+    }
+}
+
+/// Prints the diagnostics of the database. Other than a single check, a human readable report
+/// without problems is confirmed by a status line, so every change gets a visible answer.
+fn report(db: &Database, format: Format) {
+    check::report(db, format);
+    if format == Format::Human && db.files().into_iter().all(|file| db.diagnostics(file).is_empty()) {
+        eprintln!("ram: no problems found");
+    }
+    let _ = io::stdout().flush();
+}
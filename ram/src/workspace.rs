@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+ * Copyright (C) 2023 Alexander Seifarth
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// File extension of DSL source files.
+pub const EXTENSION: &str = "ram";
+
+/// Returns all `.ram` files in the given paths, sorted and without duplicates.
+/// Directories are searched recursively without following symbolic links to directories,
+/// files are taken as they are regardless of their extension.
+pub fn discover<P: AsRef<Path>>(paths: &[P]) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        if path.is_dir() {
+            discover_dir(path, &mut files)?;
+        } else if path.exists() {
+            files.push(path.to_path_buf());
+        } else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, format!("{}: no such file or directory", path.display())))
+        }
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+fn discover_dir(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        // `file_type()` does not follow symbolic links, so link cycles cannot recurse endlessly
+        if entry.file_type()?.is_dir() {
+            discover_dir(&path, files)?;
+        } else if path.extension().is_some_and(|e| e == EXTENSION) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
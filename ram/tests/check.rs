@@ -0,0 +1,124 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+ * Copyright (C) 2023 Alexander Seifarth
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+mod common;
+
+use std::path::Path;
+use std::process::{Command, Output};
+use common::TempDir;
+
+fn ram(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ram")).current_dir(dir).args(args).output().unwrap()
+}
+
+#[test]
+fn check_clean_workspace() {
+    let dir = TempDir::new("check-clean");
+    dir.write("a.ram", "system { speed = 12 }\n");
+    dir.write("sub/b.ram", "// comment only\n");
+    dir.write("notes.txt", "@@@");
+
+    let output = ram(&dir.0, &["check"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn check_reports_errors() {
+    let dir = TempDir::new("check-errors");
+    dir.write("a.ram", "ok\n");
+    dir.write("sub/b.ram", "x = 0x\n  y @\n");
+
+    let output = ram(&dir.0, &["check", "."]);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let b = Path::new(".").join("sub").join("b.ram");
+    assert_eq!(stdout, format!(
+        "{0}:1:5: error: integer literal `0x` has no digits\n{0}:2:5: error: unknown token `@`\n", b.display()));
+    // only files with problems are counted
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "ram: 2 problem(s) found in 1 file(s)\n");
+}
+
+#[test]
+fn check_json_output() {
+    let dir = TempDir::new("check-json");
+    dir.write("a.ram", "a /* open");
+
+    let output = ram(&dir.0, &["check", "--format", "json", "a.ram"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(),
+        "[{\"file\":\"a.ram\",\"line\":1,\"column\":3,\"end_line\":1,\"end_column\":10,\
+        \"severity\":\"error\",\"message\":\"block comment is not terminated\"}]\n");
+
+    dir.write("a.ram", "a");
+    let output = ram(&dir.0, &["check", "--format=json"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "[]\n");
+}
+
+#[test]
+fn usage_errors() {
+    let dir = TempDir::new("check-usage");
+    assert_eq!(ram(&dir.0, &[]).status.code(), Some(2));
+    assert_eq!(ram(&dir.0, &["frobnicate"]).status.code(), Some(2));
+    assert_eq!(ram(&dir.0, &["check", "--format", "xml"]).status.code(), Some(2));
+    assert_eq!(ram(&dir.0, &["check", "missing.ram"]).status.code(), Some(2));
+    assert_eq!(ram(&dir.0, &["help"]).status.code(), Some(0));
+}
+
+#[test]
+fn check_uses_workspace_config() {
+    let dir = TempDir::new("check-config");
+    dir.write("ram.toml", "[workspace]\nroots = [\"model\"]\n");
+    dir.write("model/a.ram", "a @");
+    dir.write("other/b.ram", "b @");
+
+    let output = ram(&dir.0, &["check"]);
+    assert_eq!(output.status.code(), Some(1));
+    let a = Path::new(".").join("model").join("a.ram");
+    assert_eq!(String::from_utf8(output.stdout).unwrap(),
+        format!("{}:1:3: error: unknown token `@`\n", a.display()));
+
+    // the configuration is also found from a sub directory
+    let output = ram(&dir.0.join("other"), &["check"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stdout).unwrap().contains("a.ram:1:3:"));
+
+    // explicit paths take precedence
+    let output = ram(&dir.0, &["check", "other"]);
+    assert!(String::from_utf8(output.stdout).unwrap().starts_with("other"));
+}
+
+#[test]
+fn check_reports_invalid_config() {
+    let dir = TempDir::new("check-invalid-config");
+    dir.write("ram.toml", "[workspace]\nroots = [\"model\"]\n");
+
+    let output = ram(&dir.0, &["check"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(),
+        "ram.toml:2:10: error: source root `model` does not exist\n");
+
+    let output = ram(&dir.0, &["check", "--format", "json"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stdout).unwrap()
+        .starts_with("[{\"file\":\"ram.toml\",\"line\":2,\"column\":10,\"end_line\":2,\"end_column\":17,"));
+}
+
+#[cfg(unix)]
+#[test]
+fn check_skips_symlinked_directories() {
+    let dir = TempDir::new("check-symlink");
+    dir.write("model/a.ram", "@\n");
+    std::os::unix::fs::symlink("..", dir.0.join("model").join("loop")).unwrap();
+
+    // the file is found once and not again through the link cycle
+    let output = ram(&dir.0, &["check"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "./model/a.ram:1:1: error: unknown token `@`\n");
+}
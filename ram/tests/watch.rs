@@ -0,0 +1,182 @@
+// SPDX-License-Identifier: MPL-2.0
+/*
+ * Copyright (C) 2023 Alexander Seifarth
+ *
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+mod common;
+
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+use common::TempDir;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Running `ram check --watch`, killed when dropped.
+struct Watch {
+    dir: TempDir,
+    child: Child,
+    lines: Receiver<String>
+}
+
+impl Watch {
+    /// Runs `ram check --watch --format json` in a new directory with the given files.
+    fn start(name: &str, files: &[(&str, &str)]) -> Self {
+        Watch::start_in(name, files, "", &["--format", "json"])
+    }
+
+    /// Runs `ram check --watch` with further `args` in the subdirectory `cwd` (empty for the
+    /// directory itself) of a new directory with the given files.
+    fn start_in(name: &str, files: &[(&str, &str)], cwd: &str, args: &[&str]) -> Self {
+        let dir = TempDir::new(&format!("watch-{}", name));
+        fs::create_dir_all(dir.0.join(cwd)).unwrap();
+        for (name, text) in files {
+            dir.write(name, text);
+        }
+        let mut child = Command::new(env!("CARGO_BIN_EXE_ram"))
+            .current_dir(dir.0.join(cwd))
+            .args(["check", "--watch"])
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        // lines of stdout and stderr are received in one channel
+        let (sender, lines) = mpsc::channel();
+        forward(child.stdout.take().unwrap(), sender.clone());
+        forward(child.stderr.take().unwrap(), sender);
+        Watch{dir, child, lines}
+    }
+
+    /// Waits for the next report that satisfies `condition`.
+    fn expect(&self, condition: impl Fn(&str) -> bool) -> String {
+        loop {
+            let line = self.lines.recv_timeout(TIMEOUT).expect("no matching report");
+            if condition(&line) {
+                return line
+            }
+        }
+    }
+}
+
+fn forward(output: impl Read + Send + 'static, sender: Sender<String>) {
+    thread::spawn(move || {
+        for line in BufReader::new(output).lines() {
+            if sender.send(line.unwrap()).is_err() {
+                break
+            }
+        }
+    });
+}
+
+impl Drop for Watch {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn watch_reports_changes() {
+    let watch = Watch::start("changes", &[("a.ram", "a { b }")]);
+    assert_eq!(watch.expect(|_| true), "[]");
+
+    watch.dir.write("a.ram", "a { @ }");
+    let report = watch.expect(|l| l != "[]");
+    assert!(report.contains("\"file\":\"./a.ram\",\"line\":1,\"column\":5"), "{}", report);
+    assert!(report.contains("unknown token `@`"), "{}", report);
+
+    watch.dir.write("a.ram", "a { b }");
+    watch.expect(|l| l == "[]");
+}
+
+#[test]
+fn watch_discovers_new_and_removed_files() {
+    let watch = Watch::start("files", &[]);
+    assert_eq!(watch.expect(|_| true), "[]");
+
+    fs::create_dir(watch.dir.0.join("sub")).unwrap();
+    watch.dir.write("sub/b.ram", "0x");
+    watch.dir.write("ignored.txt", "@");
+    let report = watch.expect(|l| l != "[]");
+    assert!(report.starts_with("[{\"file\":\"./sub/b.ram\""), "{}", report);
+    assert_eq!(report.matches("\"file\"").count(), 1, "{}", report);
+
+    fs::remove_dir_all(watch.dir.0.join("sub")).unwrap();
+    watch.expect(|l| l == "[]");
+}
+
+#[test]
+fn watch_root_outside_current_directory() {
+    let watch = Watch::start_in("parent", &[("shared/a.ram", "a")], "proj", &["--format", "json", "../shared"]);
+    assert_eq!(watch.expect(|_| true), "[]");
+
+    watch.dir.write("shared/a.ram", "@");
+    let report = watch.expect(|l| l != "[]");
+    assert!(report.starts_with("[{\"file\":\"../shared/a.ram\""), "{}", report);
+    assert_eq!(report.matches("\"file\"").count(), 1, "{}", report);
+}
+
+#[test]
+fn watch_confirms_every_human_report() {
+    let watch = Watch::start_in("human", &[("a.ram", "a"), ("b.ram", "b")], "", &[]);
+    watch.expect(|l| l == "ram: no problems found");
+
+    watch.dir.write("a.ram", "@");
+    watch.expect(|l| l == "./a.ram:1:1: error: unknown token `@`");
+    watch.expect(|l| l == "ram: 1 problem(s) found in 1 file(s)");
+
+    watch.dir.write("a.ram", "a");
+    watch.expect(|l| l == "ram: no problems found");
+}
+
+#[test]
+fn watch_file_root_saved_by_rename() {
+    let watch = Watch::start_in("rename", &[("m/a.ram", "a")], "", &["--format", "json", "m/a.ram"]);
+    assert_eq!(watch.expect(|_| true), "[]");
+
+    // editors often write a new file and rename it over the old one
+    watch.dir.write("m/a.ram.tmp", "@");
+    fs::rename(watch.dir.0.join("m/a.ram.tmp"), watch.dir.0.join("m/a.ram")).unwrap();
+    let report = watch.expect(|l| l != "[]");
+    assert!(report.starts_with("[{\"file\":\"m/a.ram\""), "{}", report);
+    assert!(report.contains("unknown token `@`"), "{}", report);
+
+    watch.dir.write("m/a.ram.tmp", "0x");
+    fs::rename(watch.dir.0.join("m/a.ram.tmp"), watch.dir.0.join("m/a.ram")).unwrap();
+    let report = watch.expect(|l| l.contains("has no digits"));
+    assert_eq!(report.matches("\"file\"").count(), 1, "{}", report);
+
+    watch.dir.write("m/a.ram", "a");
+    watch.expect(|l| l == "[]");
+}
+
+#[cfg(unix)]
+#[test]
+fn watch_skips_symlinked_directories() {
+    let watch = Watch::start("symlink", &[("model/a.ram", "@")]);
+    watch.expect(|l| l.contains("unknown token"));
+
+    std::os::unix::fs::symlink("..", watch.dir.0.join("model").join("loop")).unwrap();
+    watch.dir.write("model/a.ram", "0x");
+    // the file is reported once and not again through the link cycle
+    let report = watch.expect(|l| l.contains("has no digits"));
+    assert!(report.starts_with("[{\"file\":\"./model/a.ram\""), "{}", report);
+    assert_eq!(report.matches("\"file\"").count(), 1, "{}", report);
+}
+
+#[test]
+fn watch_file_roots_regardless_of_extension() {
+    let watch = Watch::start_in("extension", &[("a.ram", "a"), ("n.txt", "n")], "", &["--format", "json", "a.ram", "n.txt"]);
+    assert_eq!(watch.expect(|_| true), "[]");
+
+    watch.dir.write("n.txt", "@");
+    let report = watch.expect(|l| l != "[]");
+    assert!(report.starts_with("[{\"file\":\"n.txt\""), "{}", report);
+}